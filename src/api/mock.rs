@@ -0,0 +1,218 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use grammers_session::PackedChat;
+
+use crate::api::{Action, Api, CallbackAnswer, CallbackQuery, Chat, Message, OutgoingMessage, Update, User};
+use crate::error::Result;
+
+
+// A request received by `MockApi`
+#[derive(Clone, Debug, PartialEq)]
+pub enum Request {
+    SendMessage {
+        chat_id: i64,
+        message: OutgoingMessage,
+    },
+    EditMessage {
+        chat_id: i64,
+        message_id: Option<i32>,
+        message: OutgoingMessage,
+    },
+    AnswerCallback {
+        chat_id: i64,
+        answer: CallbackAnswer,
+    },
+    DownloadMedia {
+        message_id: i32,
+        path: PathBuf,
+    },
+}
+
+// Records every request instead of sending it to Telegram
+pub struct MockApi {
+    requests: Mutex<Vec<Request>>,
+    last_message_id: Mutex<i32>,
+    members_count: Option<i64>,
+    // Users that are admins of every group
+    admins: HashSet<i64>,
+}
+
+impl MockApi {
+    pub fn new() -> Self {
+        Self {
+            requests: Mutex::new(Vec::new()),
+            last_message_id: Mutex::new(0),
+            members_count: None,
+            admins: HashSet::new(),
+        }
+    }
+
+    // Set what `members_count` answers for every group
+    pub fn with_members_count(mut self, members_count: i64) -> Self {
+        self.members_count = Some(members_count);
+
+        self
+    }
+
+    // Make a user admin of every group
+    pub fn with_admin(mut self, user_id: i64) -> Self {
+        self.admins.insert(user_id);
+
+        self
+    }
+
+    // Get the requests received so far
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    // Get the requests received so far and forget them
+    pub fn take_requests(&self) -> Vec<Request> {
+        std::mem::take(&mut *self.requests.lock().unwrap())
+    }
+
+    fn record(&self, request: Request) {
+        self.requests.lock().unwrap().push(request);
+    }
+}
+
+impl Default for MockApi {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Api for MockApi {
+    async fn send_message(&self, chat: PackedChat, message: OutgoingMessage) -> Result<i32> {
+        self.record(Request::SendMessage {
+            chat_id: chat.id,
+            message: message,
+        });
+
+        let mut last_message_id = self.last_message_id.lock().unwrap();
+        *last_message_id += 1;
+
+        Ok(*last_message_id)
+    }
+
+    async fn edit_message(&self, chat: PackedChat, message_id: i32, message: OutgoingMessage) -> Result<()> {
+        self.record(Request::EditMessage {
+            chat_id: chat.id,
+            message_id: Some(message_id),
+            message: message,
+        });
+
+        Ok(())
+    }
+
+    async fn edit_callback_message(&self, callback: &CallbackQuery, message: OutgoingMessage) -> Result<()> {
+        self.record(Request::EditMessage {
+            chat_id: callback.chat.id(),
+            message_id: callback.message_id,
+            message: message,
+        });
+
+        Ok(())
+    }
+
+    async fn answer_callback(&self, callback: &CallbackQuery, answer: CallbackAnswer) -> Result<()> {
+        self.record(Request::AnswerCallback {
+            chat_id: callback.chat.id(),
+            answer: answer,
+        });
+
+        Ok(())
+    }
+
+    async fn download_media(&self, message: &Message, path: &Path) -> Result<bool> {
+        if message.document.is_none() {
+            return Ok(false);
+        }
+
+        self.record(Request::DownloadMedia {
+            message_id: message.id,
+            path: path.to_path_buf(),
+        });
+
+        Ok(true)
+    }
+
+    async fn members_count(&self, _chat: PackedChat) -> Result<Option<i64>> {
+        Ok(self.members_count)
+    }
+
+    async fn is_admin(&self, _chat: PackedChat, user: PackedChat) -> Result<bool> {
+        Ok(self.admins.contains(&user.id))
+    }
+
+    // Only the messages sent through the mock are known, and they are all outgoing
+    async fn reply_to_message(&self, message: &Message) -> Result<Option<Message>> {
+        let reply_to = match message.reply_to_message_id {
+            Some(reply_to) if reply_to <= *self.last_message_id.lock().unwrap() => reply_to,
+            _ => return Ok(None),
+        };
+
+        Ok(Some(Message {
+            id: reply_to,
+            chat: message.chat.clone(),
+            sender: None,
+            text: String::new(),
+            outgoing: true,
+            reply_to_message_id: None,
+            document: None,
+            action: None,
+            raw: None,
+        }))
+    }
+}
+
+// Build a message sent by `sender` in `chat`
+pub fn message(id: i32, chat: Chat, sender: User, text: &str) -> Message {
+    Message {
+        id: id,
+        chat: chat,
+        sender: Some(Chat::User(sender)),
+        text: text.to_string(),
+        outgoing: false,
+        reply_to_message_id: None,
+        document: None,
+        action: None,
+        raw: None,
+    }
+}
+
+// Build a service message update, like the bot being added to a group
+pub fn service_message(id: i32, chat: Chat, sender: User, action: Action) -> Update {
+    let mut message = message(id, chat, sender, "");
+    message.action = Some(action);
+
+    Update::NewMessage(message)
+}
+
+// Build a private message update
+pub fn private_message(id: i32, sender: User, text: &str) -> Update {
+    Update::NewMessage(message(id, Chat::User(sender.clone()), sender, text))
+}
+
+// Build a group message update
+pub fn group_message(id: i32, chat: Chat, sender: User, text: &str) -> Update {
+    Update::NewMessage(message(id, chat, sender, text))
+}
+
+// Build a callback update, pressed on the message `message_id`
+pub fn callback(message_id: i32, chat: Chat, sender: User, data: &str) -> Update {
+    Update::CallbackQuery(CallbackQuery {
+        chat: chat,
+        sender: Chat::User(sender),
+        data: data.as_bytes().to_vec(),
+        message_id: Some(message_id),
+        raw: None,
+    })
+}
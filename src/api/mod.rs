@@ -0,0 +1,274 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use grammers_client::types;
+use grammers_session::{PackedChat, PackedType};
+
+use crate::error::Result;
+use crate::utils::Keyboard;
+
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+mod telegram;
+
+
+// The requests the handlers make to Telegram, implemented by `grammers_client::Client`
+// and by `mock::MockApi` in the tests
+#[async_trait]
+pub trait Api: Send + Sync {
+    // Send a message, returns its ID
+    async fn send_message(&self, chat: PackedChat, message: OutgoingMessage) -> Result<i32>;
+
+    // Replace the text and keyboard of a message
+    async fn edit_message(&self, chat: PackedChat, message_id: i32, message: OutgoingMessage) -> Result<()>;
+
+    // Replace the message a callback button belongs to
+    async fn edit_callback_message(&self, callback: &CallbackQuery, message: OutgoingMessage) -> Result<()>;
+
+    // Stop the loading animation of a callback button, showing the answer if any
+    async fn answer_callback(&self, callback: &CallbackQuery, answer: CallbackAnswer) -> Result<()>;
+
+    // Download the media of a message, returns `false` if it has none
+    async fn download_media(&self, message: &Message, path: &Path) -> Result<bool>;
+
+    // Get how many members a group has
+    async fn members_count(&self, chat: PackedChat) -> Result<Option<i64>>;
+
+    // Whether a user is the creator or an admin of a group
+    async fn is_admin(&self, chat: PackedChat, user: PackedChat) -> Result<bool>;
+
+    // Get the message a message replies to, if it still exists
+    async fn reply_to_message(&self, message: &Message) -> Result<Option<Message>>;
+}
+
+pub enum Update {
+    NewMessage(Message),
+    MessageEdited(Message),
+    CallbackQuery(CallbackQuery),
+    // Someone left or was removed from a group, sent without a service message for supergroups
+    ParticipantLeft {
+        chat_id: i64,
+        user_id: i64,
+    },
+    // Updates the handlers do not know about
+    Other,
+}
+
+impl Update {
+    // Get the chat the update happened in
+    pub fn chat(&self) -> Option<&Chat> {
+        match self {
+            Self::NewMessage(message) | Self::MessageEdited(message) => Some(&message.chat),
+            Self::CallbackQuery(callback) => Some(&callback.chat),
+            Self::ParticipantLeft { .. } | Self::Other => None,
+        }
+    }
+
+    // Get who sent the update
+    pub fn sender(&self) -> Option<&Chat> {
+        match self {
+            Self::NewMessage(message) | Self::MessageEdited(message) => message.sender.as_ref(),
+            Self::CallbackQuery(callback) => Some(&callback.sender),
+            Self::ParticipantLeft { .. } | Self::Other => None,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Message {
+    pub id: i32,
+    pub chat: Chat,
+    pub sender: Option<Chat>,
+    pub text: String,
+    // Whether the bot sent the message
+    pub outgoing: bool,
+    pub reply_to_message_id: Option<i32>,
+    // Name of the attached document, if any
+    pub document: Option<String>,
+    // What happened, if it is a service message
+    pub action: Option<Action>,
+    pub(crate) raw: Option<types::Message>,
+}
+
+// The service messages the bot cares about
+#[derive(Clone, Debug, PartialEq)]
+pub enum Action {
+    // Users were added to the group, or it was created with them
+    MembersAdded(Vec<i64>),
+    // A user left or was removed from the group
+    MemberLeft(i64),
+    // The group became a supergroup with this ID
+    MigratedTo(i64),
+    Other,
+}
+
+#[derive(Clone)]
+pub struct CallbackQuery {
+    pub chat: Chat,
+    pub sender: Chat,
+    pub data: Vec<u8>,
+    // The message the button belongs to
+    pub message_id: Option<i32>,
+    pub(crate) raw: Option<types::CallbackQuery>,
+}
+
+#[derive(Clone, Debug)]
+pub enum Chat {
+    User(User),
+    Group(Group),
+    Channel(Channel),
+}
+
+impl Chat {
+    pub fn id(&self) -> i64 {
+        self.pack().id
+    }
+
+    // Get what is needed to send requests to the chat
+    pub fn pack(&self) -> PackedChat {
+        match self {
+            Self::User(user) => user.packed,
+            Self::Group(group) => group.packed,
+            Self::Channel(channel) => channel.packed,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct User {
+    pub id: i64,
+    pub first_name: String,
+    pub last_name: Option<String>,
+    pub username: Option<String>,
+    pub lang_code: Option<String>,
+    pub packed: PackedChat,
+}
+
+impl User {
+    pub fn new(id: i64, first_name: &str) -> Self {
+        Self {
+            id: id,
+            first_name: first_name.to_string(),
+            last_name: None,
+            username: None,
+            lang_code: None,
+            packed: PackedChat {
+                ty: PackedType::User,
+                id: id,
+                access_hash: None,
+            },
+        }
+    }
+
+    pub fn full_name(&self) -> String {
+        match self.last_name.as_ref() {
+            Some(last_name) if !last_name.is_empty() => format!("{} {}", self.first_name, last_name),
+            _ => self.first_name.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Group {
+    pub id: i64,
+    pub title: String,
+    pub username: Option<String>,
+    pub packed: PackedChat,
+}
+
+impl Group {
+    pub fn new(id: i64, title: &str) -> Self {
+        Self {
+            id: id,
+            title: title.to_string(),
+            username: None,
+            packed: PackedChat {
+                ty: PackedType::Megagroup,
+                id: id,
+                access_hash: None,
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Channel {
+    pub id: i64,
+    pub title: String,
+    pub username: Option<String>,
+    pub packed: PackedChat,
+}
+
+impl Channel {
+    pub fn new(id: i64, title: &str) -> Self {
+        Self {
+            id: id,
+            title: title.to_string(),
+            username: None,
+            packed: PackedChat {
+                ty: PackedType::Broadcast,
+                id: id,
+                access_hash: None,
+            },
+        }
+    }
+}
+
+// A message to be sent or edited
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OutgoingMessage {
+    pub text: String,
+    // Whether `text` is HTML or plain text
+    pub html: bool,
+    pub reply_to: Option<i32>,
+    pub keyboard: Option<Keyboard>,
+    // File to upload and attach as a document
+    pub document: Option<PathBuf>,
+}
+
+impl OutgoingMessage {
+    pub fn text<T: Into<String>>(text: T) -> Self {
+        Self {
+            text: text.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn html<T: Into<String>>(text: T) -> Self {
+        Self {
+            text: text.into(),
+            html: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn reply_to(mut self, message_id: Option<i32>) -> Self {
+        self.reply_to = message_id;
+
+        self
+    }
+
+    pub fn reply_markup(mut self, keyboard: &Keyboard) -> Self {
+        self.keyboard = Some(keyboard.clone());
+
+        self
+    }
+
+    pub fn document<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.document = Some(path.into());
+
+        self
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CallbackAnswer {
+    pub text: Option<String>,
+    // Show the text as an alert the user must dismiss, instead of a toast
+    pub alert: bool,
+    // Open a link instead, only `t.me` links to the bot, like `t.me/MyneBot?start=...`, or games
+    pub url: Option<String>,
+}
@@ -0,0 +1,249 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use std::path::Path;
+
+use async_trait::async_trait;
+use grammers_client::{Client, InputMessage, types};
+use grammers_session::{PackedChat, PackedType};
+use grammers_tl_types as tl;
+
+use crate::api::{self, Api, CallbackAnswer, OutgoingMessage};
+use crate::error::{Error, Result};
+use crate::utils::flood;
+
+
+#[async_trait]
+impl Api for Client {
+    async fn send_message(&self, chat: PackedChat, message: OutgoingMessage) -> Result<i32> {
+        let input = to_input_message(self, message).await?;
+        let sent = flood::retry(|| self.send_message(chat, input.clone())).await?;
+
+        Ok(sent.id())
+    }
+
+    async fn edit_message(&self, chat: PackedChat, message_id: i32, message: OutgoingMessage) -> Result<()> {
+        let input = to_input_message(self, message).await?;
+        flood::retry(|| self.edit_message(chat, message_id, input.clone())).await?;
+
+        Ok(())
+    }
+
+    async fn edit_callback_message(&self, callback: &api::CallbackQuery, message: OutgoingMessage) -> Result<()> {
+        let raw = callback.raw.as_ref()
+            .ok_or_else(|| Error::other("The callback was not received from Telegram"))?;
+        let loaded = flood::retry(|| raw.load_message()).await?;
+
+        Api::edit_message(self, callback.chat.pack(), loaded.id(), message).await
+    }
+
+    async fn answer_callback(&self, callback: &api::CallbackQuery, answer: CallbackAnswer) -> Result<()> {
+        let raw = callback.raw.as_ref()
+            .ok_or_else(|| Error::other("The callback was not received from Telegram"))?;
+
+        // Not tried again after a flood wait, the query expires before it ends
+        if let Some(url) = answer.url {
+            // The answer builder of grammers can not open links
+            let request = tl::functions::messages::SetBotCallbackAnswer {
+                alert: answer.alert,
+                query_id: raw.raw.query_id,
+                message: answer.text,
+                url: Some(url),
+                cache_time: 0,
+            };
+            self.invoke(&request).await?;
+
+            return Ok(());
+        }
+
+        let request = raw.answer();
+        let request = match answer.text.as_ref() {
+            Some(text) if answer.alert => request.alert(text.as_str()),
+            Some(text) => request.text(text.as_str()),
+            None => request,
+        };
+        request.send().await?;
+
+        Ok(())
+    }
+
+    async fn download_media(&self, message: &api::Message, path: &Path) -> Result<bool> {
+        let media = match message.raw.as_ref().and_then(|raw| raw.media()) {
+            Some(media) => media,
+            None => return Ok(false),
+        };
+
+        self.download_media(&media, path).await?;
+
+        Ok(true)
+    }
+
+    async fn members_count(&self, chat: PackedChat) -> Result<Option<i64>> {
+        let total = self.iter_participants(chat).total().await?;
+
+        Ok(Some(total as i64))
+    }
+
+    async fn is_admin(&self, chat: PackedChat, user: PackedChat) -> Result<bool> {
+        match chat.ty {
+            PackedType::Megagroup | PackedType::Gigagroup | PackedType::Broadcast => {
+                let channel = chat.try_to_input_channel()
+                    .ok_or_else(|| Error::other("The chat is not a channel"))?;
+                let request = tl::functions::channels::GetParticipant {
+                    channel: channel,
+                    participant: user.to_input_peer(),
+                };
+                let tl::enums::channels::ChannelParticipant::Participant(participant) = flood::retry(|| self.invoke(&request)).await?;
+
+                Ok(matches!(participant.participant, tl::enums::ChannelParticipant::Creator(_) | tl::enums::ChannelParticipant::Admin(_)))
+            }
+            PackedType::Chat => {
+                let request = tl::functions::messages::GetFullChat {
+                    chat_id: chat.id,
+                };
+                let tl::enums::messages::ChatFull::Full(full) = flood::retry(|| self.invoke(&request)).await?;
+
+                let participants = match full.full_chat {
+                    tl::enums::ChatFull::Full(chat_full) => match chat_full.participants {
+                        tl::enums::ChatParticipants::Participants(participants) => participants.participants,
+                        _ => Vec::new(),
+                    },
+                    _ => Vec::new(),
+                };
+
+                Ok(participants.iter().any(|participant| match participant {
+                    tl::enums::ChatParticipant::Creator(creator) => creator.user_id == user.id,
+                    tl::enums::ChatParticipant::Admin(admin) => admin.user_id == user.id,
+                    _ => false,
+                }))
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn reply_to_message(&self, message: &api::Message) -> Result<Option<api::Message>> {
+        let raw = match message.raw.as_ref() {
+            Some(raw) => raw,
+            None => return Ok(None),
+        };
+
+        let reply = flood::retry(|| raw.get_reply()).await?;
+
+        Ok(reply.map(Into::into))
+    }
+}
+
+async fn to_input_message(client: &Client, message: OutgoingMessage) -> Result<InputMessage> {
+    let mut input = if message.html {
+        InputMessage::html(message.text)
+    } else {
+        InputMessage::text(message.text)
+    };
+
+    input = input.reply_to(message.reply_to);
+    if let Some(keyboard) = message.keyboard.as_ref() {
+        input = input.reply_markup(&keyboard.to_markup());
+    }
+    if let Some(path) = message.document.as_ref() {
+        input = input.document(client.upload_file(path).await?);
+    }
+
+    Ok(input)
+}
+
+impl From<grammers_client::Update> for api::Update {
+    fn from(update: grammers_client::Update) -> Self {
+        match update {
+            grammers_client::Update::NewMessage(message) => Self::NewMessage(message.into()),
+            grammers_client::Update::MessageEdited(message) => Self::MessageEdited(message.into()),
+            grammers_client::Update::CallbackQuery(callback) => Self::CallbackQuery(callback.into()),
+            grammers_client::Update::Raw(tl::enums::Update::ChannelParticipant(update)) => match update.new_participant {
+                None | Some(tl::enums::ChannelParticipant::Left(_)) | Some(tl::enums::ChannelParticipant::Banned(_)) => Self::ParticipantLeft {
+                    chat_id: update.channel_id,
+                    user_id: update.user_id,
+                },
+                Some(_) => Self::Other,
+            },
+            grammers_client::Update::Raw(tl::enums::Update::ChatParticipant(update)) if update.new_participant.is_none() => Self::ParticipantLeft {
+                chat_id: update.chat_id,
+                user_id: update.user_id,
+            },
+            _ => Self::Other,
+        }
+    }
+}
+
+impl From<types::Message> for api::Message {
+    fn from(message: types::Message) -> Self {
+        let document = match message.media() {
+            Some(types::Media::Document(document)) => Some(document.name().to_string()),
+            _ => None,
+        };
+        let action = message.action().map(|action| match action {
+            tl::enums::MessageAction::ChatCreate(create) => api::Action::MembersAdded(create.users.clone()),
+            tl::enums::MessageAction::ChatAddUser(add) => api::Action::MembersAdded(add.users.clone()),
+            tl::enums::MessageAction::ChatDeleteUser(delete) => api::Action::MemberLeft(delete.user_id),
+            tl::enums::MessageAction::ChatMigrateTo(migrate) => api::Action::MigratedTo(migrate.channel_id),
+            _ => api::Action::Other,
+        });
+
+        Self {
+            id: message.id(),
+            chat: message.chat().into(),
+            sender: message.sender().map(Into::into),
+            text: message.text().to_string(),
+            outgoing: message.outgoing(),
+            reply_to_message_id: message.reply_to_message_id(),
+            document: document,
+            action: action,
+            raw: Some(message),
+        }
+    }
+}
+
+impl From<types::CallbackQuery> for api::CallbackQuery {
+    fn from(callback: types::CallbackQuery) -> Self {
+        Self {
+            chat: callback.chat().clone().into(),
+            sender: callback.sender().clone().into(),
+            data: callback.data().to_vec(),
+            message_id: Some(callback.raw.msg_id),
+            raw: Some(callback),
+        }
+    }
+}
+
+impl From<types::Chat> for api::Chat {
+    fn from(chat: types::Chat) -> Self {
+        let packed = chat.pack();
+
+        match chat {
+            types::Chat::User(user) => Self::User(user.into()),
+            types::Chat::Group(group) => Self::Group(api::Group {
+                id: group.id(),
+                title: group.title().to_string(),
+                username: group.username().map(String::from),
+                packed: packed,
+            }),
+            types::Chat::Channel(channel) => Self::Channel(api::Channel {
+                id: channel.id(),
+                title: channel.title().to_string(),
+                username: channel.username().map(String::from),
+                packed: packed,
+            }),
+        }
+    }
+}
+
+impl From<types::User> for api::User {
+    fn from(user: types::User) -> Self {
+        Self {
+            id: user.id(),
+            first_name: user.first_name().to_string(),
+            last_name: user.last_name().map(String::from),
+            username: user.username().map(String::from),
+            lang_code: user.lang_code().map(String::from),
+            packed: user.pack(),
+        }
+    }
+}
@@ -0,0 +1,498 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::sync::{Arc, RwLock};
+
+use lazy_static::lazy_static;
+use serde_derive::{Deserialize, Serialize};
+use toml::Value;
+
+use crate::database;
+use crate::plugins;
+
+
+// Prefix of the environment variables that override the configuration, like `MYNE_GRAMMERS_API_HASH`
+pub const ENV_PREFIX: &str = "MYNE_";
+
+lazy_static! {
+    static ref CONFIG: RwLock<Option<Arc<Config>>> = RwLock::new(None);
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[serde(default)]
+pub struct Config {
+    pub grammers: Grammers,
+    pub myne: Myne,
+    pub logging: Logging,
+    pub database: Database,
+    pub storage: Storage,
+    pub limits: Limits,
+    pub admins: Admins,
+    pub features: Features,
+    pub plugins: Plugins,
+    pub locales: Locales,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[serde(default)]
+pub struct Grammers {
+    pub api_id: i32,
+    pub api_hash: String,
+    pub bot_token: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct Myne {
+    // Characters that start a command, like `/start`
+    pub prefixes: Vec<String>,
+}
+
+impl Default for Myne {
+    fn default() -> Self {
+        Self {
+            prefixes: vec!["/".to_string()],
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct Logging {
+    // Minimum level of the messages, `error`, `warn`, `info`, `debug` or `trace`
+    pub level: String,
+    // Write each message as a JSON object instead of a line of text
+    pub json: bool,
+    // Levels for specific modules, like `grammers_session = "warn"`
+    pub modules: BTreeMap<String, String>,
+    pub file: LogFile,
+}
+
+impl Default for Logging {
+    fn default() -> Self {
+        Self {
+            level: "info".to_string(),
+            json: false,
+            modules: BTreeMap::new(),
+            file: LogFile::default(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct LogFile {
+    // Path of the log file, logs only to stdout if empty
+    pub path: String,
+    // Size in bytes the file can reach before being rolled
+    pub max_size: u64,
+    // How many rolled files are kept
+    pub max_files: u32,
+    // Days a rolled file is kept, forever if 0
+    pub max_age: u64,
+}
+
+impl Default for LogFile {
+    fn default() -> Self {
+        Self {
+            path: String::new(),
+            max_size: 10 * 1024 * 1024,
+            max_files: 5,
+            max_age: 30,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct Database {
+    // Path of the SQLite database
+    pub path: String,
+}
+
+impl Default for Database {
+    fn default() -> Self {
+        Self {
+            path: database::DEFAULT_PATH.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct Storage {
+    // Path of the Telegram session file
+    pub session: String,
+    // Directory where the database backups are written
+    pub backups: String,
+    // Directory of the books, each named by its ID, like `12.epub`
+    pub books: String,
+}
+
+impl Default for Storage {
+    fn default() -> Self {
+        Self {
+            session: "myne_books.session".to_string(),
+            backups: "./backups".to_string(),
+            books: "./books".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct Limits {
+    // Seconds a chat profile is kept in memory before being written again
+    pub profile_interval: u64,
+    // Flood waits up to this many seconds are slept through by grammers
+    pub flood_sleep_threshold: u32,
+    // Seconds to wait for the updates being handled when shutting down
+    pub shutdown_timeout: u64,
+    // Threads handling the updates, as many as CPU cores if 0
+    pub workers: usize,
+    // Updates handled at the same time, across all chats
+    pub concurrency: usize,
+    // Updates waiting to be handled in each chat, the next ones are dropped while it is full
+    pub queue: usize,
+    // Updates a user can send in a burst, no limit if 0
+    pub user_burst: u32,
+    // Updates per second a user can keep sending after the burst
+    pub user_rate: f64,
+    // Updates a chat can receive in a burst, no limit if 0
+    pub chat_burst: u32,
+    // Updates per second a chat can keep receiving after the burst
+    pub chat_rate: f64,
+    // Times a request is tried again after a flood wait
+    pub flood_retries: u32,
+    // Longest flood wait, in seconds, a request waits for before trying again; it fails at once above it
+    pub max_flood_wait: u64,
+    // Seconds the bot waits for the answer to a question, like a translation, before forgetting it
+    pub conversation_timeout: u64,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            profile_interval: 5 * 60,
+            flood_sleep_threshold: 120,
+            shutdown_timeout: 10,
+            workers: 0,
+            concurrency: 16,
+            queue: 32,
+            user_burst: 5,
+            user_rate: 1.0,
+            chat_burst: 20,
+            chat_rate: 5.0,
+            flood_retries: 3,
+            max_flood_wait: 300,
+            conversation_timeout: 15 * 60,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[serde(default)]
+pub struct Admins {
+    // Telegram IDs allowed to use the owner commands
+    pub owners: Vec<i64>,
+    // Telegram IDs allowed to submit translations with /translate, besides the owners
+    pub translators: Vec<i64>,
+    // Chat the handler errors are reported to, as a Bot API ID like `-1001234567890`, disabled if 0
+    pub log_chat: i64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy)]
+#[serde(default)]
+pub struct Features {
+    // Refresh the name, username and title of the users and groups on every update
+    pub refresh_profiles: bool,
+    // Fetch the members count of the groups, costs a request per profile write
+    pub members_count: bool,
+}
+
+impl Default for Features {
+    fn default() -> Self {
+        Self {
+            refresh_profiles: true,
+            members_count: true,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[serde(default)]
+pub struct Plugins {
+    // Plugins loaded even if they are disabled by default
+    pub enabled: Vec<String>,
+    // Plugins not loaded, unless enabled at runtime by an owner
+    pub disabled: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct Locales {
+    // Directory with locale files loaded over the bundled ones, disabled if empty
+    pub directory: String,
+    // Seconds between checks for changed files in the directory, only reloaded by /reload_locales if 0
+    pub reload_interval: u64,
+}
+
+impl Default for Locales {
+    fn default() -> Self {
+        Self {
+            directory: String::new(),
+            reload_interval: 30,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    // The file could not be read
+    Io(String, io::Error),
+    // The file or an environment variable is not valid TOML for the configuration
+    Parse(String),
+    // The configuration was loaded but some values are not accepted
+    Invalid(Vec<String>),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(path, e) => write!(f, "Failed to read the configuration file {}: {}", path, e),
+            Self::Parse(e) => write!(f, "Failed to parse the configuration: {}", e),
+            Self::Invalid(problems) => {
+                write!(f, "Invalid configuration:")?;
+                for problem in problems.iter() {
+                    write!(f, "\n  - {}", problem)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl Config {
+    // Check the values that would only fail later, while running
+    pub fn validate(&self) -> Result<(), Error> {
+        let mut problems = Vec::new();
+
+        if self.grammers.api_id <= 0 {
+            problems.push("`grammers.api_id` must be set, get it at https://my.telegram.org".to_string());
+        }
+        if self.grammers.api_hash.trim().is_empty() {
+            problems.push("`grammers.api_hash` must be set, get it at https://my.telegram.org".to_string());
+        }
+        if !self.grammers.bot_token.contains(':') {
+            problems.push("`grammers.bot_token` must be a bot token like `123456:ABC-DEF`, get it from @BotFather".to_string());
+        }
+
+        if self.myne.prefixes.is_empty() {
+            problems.push("`myne.prefixes` must have at least one prefix".to_string());
+        }
+        for prefix in self.myne.prefixes.iter() {
+            if prefix.chars().count() != 1 {
+                problems.push(format!("`myne.prefixes` must have single characters, found `{}`", prefix));
+            }
+        }
+
+        if !is_level(&self.logging.level) {
+            problems.push(format!("`logging.level` must be `error`, `warn`, `info`, `debug`, `trace` or `off`, found `{}`", self.logging.level));
+        }
+        for (module, level) in self.logging.modules.iter() {
+            if !is_level(level) {
+                problems.push(format!("`logging.modules.{}` must be `error`, `warn`, `info`, `debug`, `trace` or `off`, found `{}`", module, level));
+            }
+        }
+        if !self.logging.file.path.is_empty() {
+            if self.logging.file.max_size == 0 {
+                problems.push("`logging.file.max_size` must be greater than 0".to_string());
+            }
+            if self.logging.file.max_files == 0 {
+                problems.push("`logging.file.max_files` must be greater than 0".to_string());
+            }
+        }
+
+        if self.limits.concurrency == 0 {
+            problems.push("`limits.concurrency` must be greater than 0".to_string());
+        }
+        if self.limits.queue == 0 {
+            problems.push("`limits.queue` must be greater than 0".to_string());
+        }
+        if self.limits.conversation_timeout == 0 {
+            problems.push("`limits.conversation_timeout` must be greater than 0".to_string());
+        }
+
+        // Without a rate the burst would never refill
+        if self.limits.user_burst > 0 && self.limits.user_rate <= 0.0 {
+            problems.push("`limits.user_rate` must be greater than 0 when `limits.user_burst` is set".to_string());
+        }
+        if self.limits.chat_burst > 0 && self.limits.chat_rate <= 0.0 {
+            problems.push("`limits.chat_rate` must be greater than 0 when `limits.chat_burst` is set".to_string());
+        }
+
+        if self.database.path.trim().is_empty() {
+            problems.push("`database.path` must not be empty".to_string());
+        }
+        if self.storage.session.trim().is_empty() {
+            problems.push("`storage.session` must not be empty".to_string());
+        }
+        if self.storage.backups.trim().is_empty() {
+            problems.push("`storage.backups` must not be empty".to_string());
+        }
+        if self.storage.books.trim().is_empty() {
+            problems.push("`storage.books` must not be empty".to_string());
+        }
+
+        for name in self.plugins.enabled.iter() {
+            if self.plugins.disabled.contains(name) {
+                problems.push(format!("`plugins.enabled` and `plugins.disabled` both have `{}`", name));
+            }
+        }
+        for name in self.plugins.disabled.iter() {
+            if plugins::PROTECTED.contains(&name.as_str()) {
+                problems.push(format!("`plugins.disabled` can not have `{}`, that plugin can not be disabled", name));
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Invalid(problems))
+        }
+    }
+}
+
+fn is_level(level: &str) -> bool {
+    ["off", "error", "warn", "info", "debug", "trace"].contains(&level.to_lowercase().as_str())
+}
+
+// Read the configuration from a TOML file, apply the environment overrides and validate it
+pub fn load(path: &str) -> Result<Config, Error> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| Error::Io(path.to_string(), e))?;
+
+    from_str(&content, env::vars())
+}
+
+// Parse the configuration, overriding it with the `MYNE_*` variables of `vars`
+pub fn from_str<I: IntoIterator<Item = (String, String)>>(content: &str, vars: I) -> Result<Config, Error> {
+    let mut value: Value = toml::from_str(content)
+        .map_err(|e| Error::Parse(e.to_string()))?;
+    let schema = Value::try_from(Config::default())
+        .map_err(|e| Error::Parse(e.to_string()))?;
+
+    for (name, raw) in vars {
+        if let Some(name) = name.strip_prefix(ENV_PREFIX) {
+            apply_override(&mut value, &schema, name, &raw)?;
+        }
+    }
+
+    let config: Config = value.try_into()
+        .map_err(|e: toml::de::Error| Error::Parse(e.to_string()))?;
+    config.validate()?;
+
+    Ok(config)
+}
+
+// Set the key named by an environment variable, `GRAMMERS_API_ID` sets `grammers.api_id`
+// and `LOGGING_FILE__PATH` sets `logging.file.path`
+fn apply_override(value: &mut Value, schema: &Value, name: &str, raw: &str) -> Result<(), Error> {
+    let name = name.to_lowercase();
+    let (section, key) = match name.split_once('_') {
+        Some(parts) => parts,
+        None => return Ok(()),
+    };
+
+    let mut path = vec![section];
+    path.extend(key.split("__"));
+
+    let mut expected = Some(schema);
+    for part in path.iter() {
+        expected = expected.and_then(|value| value.get(*part));
+    }
+
+    let mut table = value;
+    for part in path[..path.len() - 1].iter() {
+        table = match table {
+            Value::Table(map) => map.entry(part.to_string())
+                .or_insert_with(|| Value::Table(Default::default())),
+            _ => return Err(Error::Parse(format!("`{}` is not a table", part))),
+        };
+    }
+
+    let parsed = parse_value(raw, expected)
+        .ok_or_else(|| Error::Parse(format!("`{}{}` has an invalid value `{}`", ENV_PREFIX, name.to_uppercase(), raw)))?;
+    match table {
+        Value::Table(map) => {
+            map.insert(path[path.len() - 1].to_string(), parsed);
+        }
+        _ => return Err(Error::Parse(format!("`{}` is not a table", path[..path.len() - 1].join(".")))),
+    }
+
+    Ok(())
+}
+
+// Parse an environment variable as the type the configuration expects
+fn parse_value(raw: &str, expected: Option<&Value>) -> Option<Value> {
+    match expected {
+        Some(Value::String(_)) => Some(Value::String(raw.to_string())),
+        Some(Value::Integer(_)) => raw.trim().parse().ok().map(Value::Integer),
+        Some(Value::Float(_)) => raw.trim().parse().ok().map(Value::Float),
+        Some(Value::Boolean(_)) => raw.trim().parse().ok().map(Value::Boolean),
+        Some(Value::Array(items)) => {
+            // Accept both TOML arrays, `[1, 2]`, and comma separated values, `1,2`
+            match toml::from_str::<Value>(&format!("value = {}", raw)).ok().and_then(|value| value.get("value").cloned()) {
+                Some(Value::Array(array)) => Some(Value::Array(array)),
+                _ => raw.split(',')
+                    .map(|item| parse_value(item.trim(), items.first()))
+                    .collect::<Option<Vec<Value>>>()
+                    .map(Value::Array),
+            }
+        }
+        _ => toml::from_str::<Value>(&format!("value = {}", raw))
+            .ok()
+            .and_then(|value| value.get("value").cloned())
+            .or_else(|| Some(Value::String(raw.to_string()))),
+    }
+}
+
+// Make the configuration available through `get`
+pub fn set(config: Config) {
+    *CONFIG.write().unwrap() = Some(Arc::new(config));
+}
+
+// Get the loaded configuration, or the default one if nothing was loaded; it is shared, clone it to
+// change it and `set` it again
+pub fn get() -> Arc<Config> {
+    CONFIG.read()
+        .unwrap()
+        .clone()
+        .unwrap_or_default()
+}
+
+// Check if a user is one of the bot owners
+pub fn is_owner(user_id: i64) -> bool {
+    CONFIG.read()
+        .unwrap()
+        .as_ref()
+        .map(|config| config.admins.owners.contains(&user_id))
+        .unwrap_or(false)
+}
+
+// Check if a user can submit translations, the owners always can
+pub fn is_translator(user_id: i64) -> bool {
+    CONFIG.read()
+        .unwrap()
+        .as_ref()
+        .map(|config| config.admins.owners.contains(&user_id) || config.admins.translators.contains(&user_id))
+        .unwrap_or(false)
+}
@@ -0,0 +1,107 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use std::error::Error;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+use rusqlite::DatabaseName;
+use rusqlite::backup::Progress;
+use serde_derive::{Deserialize, Serialize};
+
+use crate::database;
+use crate::database::tables;
+
+
+pub type Result<T> = std::result::Result<T, Box<dyn Error + Send + Sync>>;
+
+// Version of the export format
+const DUMP_VERSION: u32 = 1;
+
+// Every record the bot keeps, used to move the data between hosts
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Dump {
+    pub version: u32,
+    pub created_at: i64,
+    pub users: Vec<tables::User>,
+    pub groups: Vec<tables::Group>,
+    // Missing from the exports made before the channels were kept
+    #[serde(default)]
+    pub channels: Vec<tables::Channel>,
+    // The plugins enabled or disabled at runtime, in every chat or in one
+    #[serde(default)]
+    pub plugins: Vec<tables::Plugin>,
+    // The translations submitted with /translate, approved or waiting for an owner
+    #[serde(default)]
+    pub translations: Vec<tables::Translation>,
+}
+
+// Copy the database, while it is in use, to a timestamped file inside `directory`
+pub fn backup(directory: &str) -> Result<PathBuf> {
+    fs::create_dir_all(directory)?;
+
+    let path = Path::new(directory).join(format!("myne_books-{}.db3", database::timestamp()));
+
+    let dbc = database::connect()?;
+    dbc.get_conn().backup(DatabaseName::Main, &path, None)?;
+
+    Ok(path)
+}
+
+// Replace the database with a backup made by `backup`
+pub fn restore(path: &str) -> Result<()> {
+    let mut dbc = database::connect()?;
+    dbc.get_conn_mut().restore(DatabaseName::Main, path, None::<fn(Progress)>)?;
+
+    Ok(())
+}
+
+// Write every record to a JSON file
+pub fn export(path: &str) -> Result<Dump> {
+    let dump = Dump {
+        version: DUMP_VERSION,
+        created_at: database::timestamp(),
+        users: tables::User::all()?,
+        groups: tables::Group::all()?,
+        channels: tables::Channel::all()?,
+        plugins: tables::Plugin::all()?,
+        translations: tables::Translation::all()?,
+    };
+
+    if let Some(parent) = Path::new(path).parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let file = File::create(path)?;
+    serde_json::to_writer_pretty(file, &dump)?;
+
+    Ok(dump)
+}
+
+// Read the records from a JSON file made by `export`, replacing the existing ones
+pub fn import(path: &str) -> Result<Dump> {
+    let file = File::open(path)?;
+    let dump: Dump = serde_json::from_reader(file)?;
+
+    if dump.version > DUMP_VERSION {
+        return Err(format!("Unsupported export version {}, expected up to {}", dump.version, DUMP_VERSION).into());
+    }
+
+    for user in dump.users.iter() {
+        user.save()?;
+    }
+    for group in dump.groups.iter() {
+        group.save()?;
+    }
+    for channel in dump.channels.iter() {
+        channel.save()?;
+    }
+    for plugin in dump.plugins.iter() {
+        plugin.save()?;
+    }
+    for translation in dump.translations.iter() {
+        translation.save()?;
+    }
+
+    Ok(dump)
+}
@@ -3,11 +3,22 @@
 
 #![allow(unused_must_use)]
 
+use std::sync::RwLock;
+
+use lazy_static::lazy_static;
 use rusqlite::{Connection, Error};
 
 use crate::database::tables;
 
 
+// Where the database is stored unless `set_path` is called
+pub const DEFAULT_PATH: &str = "./src/database/sqlite.db3";
+
+lazy_static! {
+    static ref PATH: RwLock<String> = RwLock::new(DEFAULT_PATH.to_string());
+}
+
+
 // A client to do the SQL operations.
 pub struct Client {
     connection: Connection,
@@ -25,20 +36,52 @@ impl Client {
     pub fn get_conn(&self) -> &Connection {
         &self.connection
     }
+
+    // Get the connection, mutably
+    pub fn get_conn_mut(&mut self) -> &mut Connection {
+        &mut self.connection
+    }
     
+    // Add a column to a table created by an older version, if it is missing
+    pub fn add_column(&self, table: &str, column: &str, definition: &str) -> Result<(), Error> {
+        let mut statement = self.connection.prepare(&format!("PRAGMA table_info({})", table))?;
+        let columns = statement
+            .query_map([], |row| row.get::<_, String>(1))?
+            .collect::<Result<Vec<String>, Error>>()?;
+
+        if !columns.iter().any(|name| name == column) {
+            self.connection.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
+        }
+
+        Ok(())
+    }
+
     // Create database tables if they do not exist
     pub fn initialize(&self) -> Result<(), Error> {
         tables::User::create().expect("Failed to create the `users` table");
         tables::Group::create().expect("Failed to create the `groups` table");
+        tables::Channel::create().expect("Failed to create the `channels` table");
+        tables::Plugin::create().expect("Failed to create the `plugins` table");
+        tables::Translation::create().expect("Failed to create the `translations` table");
         
         Ok(())
     }
 }
 
+// Change the database file used by new connections
+pub fn set_path(path: &str) {
+    *PATH.write().unwrap() = path.to_string();
+}
+
+// Get the database file used by new connections
+pub fn get_path() -> String {
+    PATH.read().unwrap().clone()
+}
+
 // Open a new connection
 pub fn connect() -> Result<Client, Error> {
-    let path = "./src/database/sqlite.db3".to_string();
-    let conn = Connection::open(&path).unwrap();
+    let path = get_path();
+    let conn = Connection::open(&path)?;
     
     Ok(Client {
         connection: conn,
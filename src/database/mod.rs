@@ -1,7 +1,20 @@
 // SPDX-License-Identifier: MIT
 // Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
 
+use std::time::{SystemTime, UNIX_EPOCH};
+
 pub mod tables;
 pub mod client;
+pub mod presence;
+pub mod backup;
+
+pub use client::{Client, DEFAULT_PATH, connect, get_path, set_path};
+
 
-pub use client::{Client, connect};
+// Current UNIX timestamp, in seconds
+pub fn timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or_default()
+}
@@ -0,0 +1,107 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+#![allow(unused_must_use)]
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use lazy_static::lazy_static;
+
+use crate::config;
+use crate::database;
+use crate::database::tables;
+
+
+// Chats kept before the ones written longer than the profile interval ago start being forgotten
+const MAX_ENTRIES: usize = 10_000;
+
+lazy_static! {
+    static ref CACHE: Mutex<HashMap<i64, Entry>> = Mutex::new(HashMap::new());
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Kind {
+    User,
+    Group,
+    Channel,
+}
+
+struct Entry {
+    kind: Kind,
+    fingerprint: String,
+    written_at: Instant,
+    last_seen: i64,
+    pending: bool,
+}
+
+// Mark a chat as seen, returns `true` if its profile should be written to the database
+pub fn touch(kind: Kind, id: i64, fingerprint: &str) -> bool {
+    // How long a chat profile is trusted before being written again
+    let interval = Duration::from_secs(config::get().limits.profile_interval);
+    let mut cache = CACHE.lock().unwrap();
+
+    if cache.len() > MAX_ENTRIES {
+        evict(&mut cache, interval);
+    }
+
+    match cache.get_mut(&id) {
+        Some(entry) if entry.kind == kind && entry.fingerprint == fingerprint && entry.written_at.elapsed() < interval => {
+            entry.last_seen = database::timestamp();
+            entry.pending = true;
+
+            false
+        }
+        _ => {
+            cache.insert(id, Entry {
+                kind: kind,
+                fingerprint: fingerprint.to_string(),
+                written_at: Instant::now(),
+                last_seen: database::timestamp(),
+                pending: false,
+            });
+
+            true
+        }
+    }
+}
+
+// Forget a chat, so its profile is written on the next update
+pub fn forget(id: i64) {
+    CACHE.lock().unwrap().remove(&id);
+}
+
+// Write the `last_seen` of every chat that was seen since its last write
+pub fn flush() {
+    let mut cache = CACHE.lock().unwrap();
+
+    for (id, entry) in cache.iter_mut().filter(|(_, entry)| entry.pending) {
+        write(*id, entry);
+    }
+}
+
+// Forget the chats whose profile would be written again on their next update anyway, writing their
+// `last_seen` first
+fn evict(cache: &mut HashMap<i64, Entry>, interval: Duration) {
+    cache.retain(|id, entry| {
+        if entry.written_at.elapsed() < interval {
+            return true;
+        }
+        if entry.pending {
+            write(*id, entry);
+        }
+
+        false
+    });
+}
+
+fn write(id: i64, entry: &mut Entry) {
+    match entry.kind {
+        Kind::User => tables::User::set_last_seen(id, entry.last_seen),
+        Kind::Group => tables::Group::set_last_seen(id, entry.last_seen),
+        Kind::Channel => tables::Channel::set_last_seen(id, entry.last_seen),
+    };
+
+    entry.pending = false;
+}
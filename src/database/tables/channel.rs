@@ -0,0 +1,179 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+#![allow(dead_code)]
+#![allow(unused_must_use)]
+
+use grammers_session::{PackedChat, PackedType};
+use rusqlite::{Error, Row, params};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::database;
+
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Channel {
+    // Telegram channel ID
+    pub id: i64,
+    // Telegram channel title
+    pub title: String,
+    // Bot channel language
+    pub language: String,
+    // Telegram channel username
+    pub username: Option<String>,
+    // When the channel was first seen, UNIX timestamp
+    pub first_seen: i64,
+    // When the channel was last seen, UNIX timestamp
+    pub last_seen: i64,
+    // Telegram access hash, needed to post in the channel first
+    pub access_hash: Option<i64>,
+}
+
+impl Channel {
+    // Create the `channels` table
+    pub fn create() -> Result<(), Error> {
+        let dbc = database::connect().unwrap();
+        let conn = dbc.get_conn();
+
+        let sql = "
+        CREATE TABLE IF NOT EXISTS channels (
+                id          INTEGER PRIMARY KEY,
+                title       TEXT NOT NULL,
+                language    VARCHAR(6) NOT NULL DEFAULT \"en-GB\",
+                username    TEXT,
+                first_seen  INTEGER NOT NULL DEFAULT 0,
+                last_seen   INTEGER NOT NULL DEFAULT 0,
+                access_hash INTEGER
+        )
+        ";
+
+        conn.execute(sql, [])?;
+
+        Ok(())
+    }
+
+    // Register a `channel`
+    pub fn register(id: i64, title: &str) -> Result<(), Error> {
+        let dbc = database::connect().unwrap();
+        let conn = dbc.get_conn();
+        let now = database::timestamp();
+
+        let sql = "
+        INSERT INTO channels (id, title, first_seen, last_seen) VALUES (?, ?, ?, ?)
+        ";
+
+        conn.execute(sql, params![id, title, now, now]);
+
+        Ok(())
+    }
+
+    // Register a `channel` or refresh its profile if it already exists
+    pub fn upsert(id: i64, title: &str, username: Option<&str>, access_hash: Option<i64>) -> Result<(), Error> {
+        let dbc = database::connect().unwrap();
+        let conn = dbc.get_conn();
+        let now = database::timestamp();
+
+        let sql = "
+        INSERT INTO channels (id, title, username, first_seen, last_seen, access_hash) VALUES (?, ?, ?, ?, ?, ?)
+        ON CONFLICT (id) DO UPDATE SET
+                title = excluded.title,
+                username = excluded.username,
+                last_seen = excluded.last_seen,
+                access_hash = COALESCE(excluded.access_hash, channels.access_hash)
+        ";
+
+        conn.execute(sql, params![id, title, username, now, now, access_hash])?;
+
+        Ok(())
+    }
+
+    // Update when a `channel` was last seen
+    pub fn set_last_seen(id: i64, last_seen: i64) -> Result<(), Error> {
+        let dbc = database::connect().unwrap();
+        let conn = dbc.get_conn();
+
+        let sql = "
+        UPDATE channels SET last_seen = ? WHERE id = ?
+        ";
+
+        conn.execute(sql, params![last_seen, id])?;
+
+        Ok(())
+    }
+
+    // Get a `channel` by id
+    pub fn get(id: i64) -> Result<Self, Error> {
+        let dbc = database::connect().unwrap();
+        let conn = dbc.get_conn();
+
+        let sql = "
+        SELECT id, title, language, username, first_seen, last_seen, access_hash FROM channels WHERE id = ?
+        ";
+
+        conn.query_row(sql, params![id], Self::from_row)
+    }
+
+    // Get every `channel`
+    pub fn all() -> Result<Vec<Self>, Error> {
+        let dbc = database::connect().unwrap();
+        let conn = dbc.get_conn();
+
+        let sql = "
+        SELECT id, title, language, username, first_seen, last_seen, access_hash FROM channels
+        ";
+
+        let mut statement = conn.prepare(sql)?;
+        let rows = statement.query_map([], Self::from_row)?;
+
+        rows.collect()
+    }
+
+    // Save the `channel` as is, replacing any existing one with the same id
+    pub fn save(&self) -> Result<(), Error> {
+        let dbc = database::connect().unwrap();
+        let conn = dbc.get_conn();
+
+        let sql = "
+        INSERT OR REPLACE INTO channels (id, title, language, username, first_seen, last_seen, access_hash) VALUES (?, ?, ?, ?, ?, ?, ?)
+        ";
+
+        conn.execute(sql, params![self.id, self.title, self.language, self.username, self.first_seen, self.last_seen, self.access_hash])?;
+
+        Ok(())
+    }
+
+    // Delete a `channel` by id
+    pub fn delete(id: i64) -> Result<(), Error> {
+        let dbc = database::connect().unwrap();
+        let conn = dbc.get_conn();
+
+        let sql = "
+        DELETE FROM channels WHERE id = ?
+        ";
+
+        conn.execute(sql, params![id]);
+
+        Ok(())
+    }
+
+    // Get what is needed to post in the `channel`
+    pub fn pack(&self) -> PackedChat {
+        PackedChat {
+            ty: PackedType::Broadcast,
+            id: self.id,
+            access_hash: self.access_hash,
+        }
+    }
+
+    fn from_row(row: &Row) -> Result<Self, Error> {
+        Ok(Self {
+            id: row.get(0)?,
+            title: row.get(1)?,
+            language: row.get(2)?,
+            username: row.get(3)?,
+            first_seen: row.get(4)?,
+            last_seen: row.get(5)?,
+            access_hash: row.get(6)?,
+        })
+    }
+}
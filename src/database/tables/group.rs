@@ -4,12 +4,14 @@
 #![allow(dead_code)]
 #![allow(unused_must_use)]
 
-use rusqlite::{Error, params};
+use grammers_session::{PackedChat, PackedType};
+use rusqlite::{Error, Row, params};
+use serde_derive::{Deserialize, Serialize};
 
 use crate::database;
 
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Group {
     // Telegram group ID
     pub id: i64,
@@ -17,6 +19,25 @@ pub struct Group {
     pub title: String,
     // Bot group language
     pub language: String,
+    // Telegram group username
+    pub username: Option<String>,
+    // Telegram chat type, `group`, `supergroup` or `gigagroup`
+    pub chat_type: String,
+    // Number of members, when known
+    pub members_count: Option<i64>,
+    // When the group was first seen, UNIX timestamp
+    pub first_seen: i64,
+    // When the group was last seen, UNIX timestamp
+    pub last_seen: i64,
+    // Telegram access hash, needed to message the group first
+    pub access_hash: Option<i64>,
+    // Whether the bot is still a member of the group
+    #[serde(default = "default_active")]
+    pub active: bool,
+}
+
+fn default_active() -> bool {
+    true
 }
 
 impl Group {
@@ -27,13 +48,29 @@ impl Group {
         
         let sql = "
         CREATE TABLE IF NOT EXISTS groups (
-                id         INTEGER PRIMARY KEY,
-                title      TEXT NOT NULL,
-                language   VARCHAR(6) NOT NULL DEFAULT \"en-GB\"
+                id            INTEGER PRIMARY KEY,
+                title         TEXT NOT NULL,
+                language      VARCHAR(6) NOT NULL DEFAULT \"en-GB\",
+                username      TEXT,
+                chat_type     TEXT NOT NULL DEFAULT \"group\",
+                members_count INTEGER,
+                first_seen    INTEGER NOT NULL DEFAULT 0,
+                last_seen     INTEGER NOT NULL DEFAULT 0,
+                access_hash   INTEGER,
+                active        BOOLEAN NOT NULL DEFAULT 1
         )
         ";
         
         conn.execute(sql, []);
+
+        // Columns added after the first release
+        dbc.add_column("groups", "username", "TEXT")?;
+        dbc.add_column("groups", "chat_type", "TEXT NOT NULL DEFAULT \"group\"")?;
+        dbc.add_column("groups", "members_count", "INTEGER")?;
+        dbc.add_column("groups", "first_seen", "INTEGER NOT NULL DEFAULT 0")?;
+        dbc.add_column("groups", "last_seen", "INTEGER NOT NULL DEFAULT 0")?;
+        dbc.add_column("groups", "access_hash", "INTEGER")?;
+        dbc.add_column("groups", "active", "BOOLEAN NOT NULL DEFAULT 1")?;
         
         Ok(())
     }
@@ -42,15 +79,81 @@ impl Group {
     pub fn register(id: i64, title: &str) -> Result<(), Error> {
         let dbc = database::connect().unwrap();
         let conn = dbc.get_conn();
+        let now = database::timestamp();
         
         let sql = "
-        INSERT INTO groups (id, title) VALUES (?, ?)
+        INSERT INTO groups (id, title, first_seen, last_seen) VALUES (?, ?, ?, ?)
         ";
         
-        conn.execute(sql, params![id, title]);
+        conn.execute(sql, params![id, title, now, now]);
         
         Ok(())
     }
+
+    // Register a `group` or refresh its profile if it already exists
+    pub fn upsert(id: i64, title: &str, username: Option<&str>, chat_type: &str, members_count: Option<i64>, access_hash: Option<i64>) -> Result<(), Error> {
+        let dbc = database::connect().unwrap();
+        let conn = dbc.get_conn();
+        let now = database::timestamp();
+
+        let sql = "
+        INSERT INTO groups (id, title, username, chat_type, members_count, first_seen, last_seen, access_hash) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT (id) DO UPDATE SET
+                title = excluded.title,
+                username = excluded.username,
+                chat_type = excluded.chat_type,
+                members_count = COALESCE(excluded.members_count, groups.members_count),
+                last_seen = excluded.last_seen,
+                access_hash = COALESCE(excluded.access_hash, groups.access_hash),
+                active = 1
+        ";
+
+        conn.execute(sql, params![id, title, username, chat_type, members_count, now, now, access_hash])?;
+
+        Ok(())
+    }
+
+    // Mark whether the bot is still a member of a `group`
+    pub fn set_active(id: i64, active: bool) -> Result<(), Error> {
+        let dbc = database::connect().unwrap();
+        let conn = dbc.get_conn();
+
+        let sql = "
+        UPDATE groups SET active = ? WHERE id = ?
+        ";
+
+        conn.execute(sql, params![active, id])?;
+
+        Ok(())
+    }
+
+    // Change the language of a `group`
+    pub fn set_language(id: i64, language: &str) -> Result<(), Error> {
+        let dbc = database::connect().unwrap();
+        let conn = dbc.get_conn();
+
+        let sql = "
+        UPDATE groups SET language = ? WHERE id = ?
+        ";
+
+        conn.execute(sql, params![language, id])?;
+
+        Ok(())
+    }
+
+    // Update when a `group` was last seen
+    pub fn set_last_seen(id: i64, last_seen: i64) -> Result<(), Error> {
+        let dbc = database::connect().unwrap();
+        let conn = dbc.get_conn();
+
+        let sql = "
+        UPDATE groups SET last_seen = ? WHERE id = ?
+        ";
+
+        conn.execute(sql, params![last_seen, id])?;
+
+        Ok(())
+    }
     
     // Get a `group` by id
     pub fn get(id: i64) -> Result<Self, Error> {
@@ -58,18 +161,41 @@ impl Group {
         let conn = dbc.get_conn();
         
         let sql = "
-        SELECT * FROM groups WHERE id = ?
+        SELECT id, title, language, username, chat_type, members_count, first_seen, last_seen, access_hash, active FROM groups WHERE id = ?
         ";
     
-        conn.query_row(sql, params![id], |row| {
-            Ok(Self {
-                id: row.get(0).unwrap(),
-                title: row.get(1).unwrap(),
-                language: row.get(2).unwrap(),
-            })
-        })
+        conn.query_row(sql, params![id], Self::from_row)
     }
     
+    // Get every `group`
+    pub fn all() -> Result<Vec<Self>, Error> {
+        let dbc = database::connect().unwrap();
+        let conn = dbc.get_conn();
+
+        let sql = "
+        SELECT id, title, language, username, chat_type, members_count, first_seen, last_seen, access_hash, active FROM groups
+        ";
+
+        let mut statement = conn.prepare(sql)?;
+        let rows = statement.query_map([], Self::from_row)?;
+
+        rows.collect()
+    }
+
+    // Save the `group` as is, replacing any existing one with the same id
+    pub fn save(&self) -> Result<(), Error> {
+        let dbc = database::connect().unwrap();
+        let conn = dbc.get_conn();
+
+        let sql = "
+        INSERT OR REPLACE INTO groups (id, title, language, username, chat_type, members_count, first_seen, last_seen, access_hash, active) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ";
+
+        conn.execute(sql, params![self.id, self.title, self.language, self.username, self.chat_type, self.members_count, self.first_seen, self.last_seen, self.access_hash, self.active])?;
+
+        Ok(())
+    }
+
     // Delete a `group` by id
     pub fn delete(id: i64) -> Result<(), Error> {
         let dbc = database::connect().unwrap();
@@ -83,4 +209,34 @@ impl Group {
         
         Ok(())
     }
+
+    // Get what is needed to message the `group`
+    pub fn pack(&self) -> PackedChat {
+        let ty = match self.chat_type.as_str() {
+            "supergroup" => PackedType::Megagroup,
+            "gigagroup" => PackedType::Gigagroup,
+            _ => PackedType::Chat,
+        };
+
+        PackedChat {
+            ty: ty,
+            id: self.id,
+            access_hash: self.access_hash,
+        }
+    }
+
+    fn from_row(row: &Row) -> Result<Self, Error> {
+        Ok(Self {
+            id: row.get(0)?,
+            title: row.get(1)?,
+            language: row.get(2)?,
+            username: row.get(3)?,
+            chat_type: row.get(4)?,
+            members_count: row.get(5)?,
+            first_seen: row.get(6)?,
+            last_seen: row.get(7)?,
+            access_hash: row.get(8)?,
+            active: row.get(9)?,
+        })
+    }
 }
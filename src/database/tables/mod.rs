@@ -3,6 +3,12 @@
 
 mod user;
 mod group;
+mod channel;
+mod plugin;
+mod translation;
 
 pub use user::User;
-pub use group::Group;
\ No newline at end of file
+pub use group::Group;
+pub use channel::Channel;
+pub use plugin::{GLOBAL, Plugin};
+pub use translation::Translation;
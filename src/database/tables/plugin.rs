@@ -0,0 +1,125 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+#![allow(dead_code)]
+#![allow(unused_must_use)]
+
+use rusqlite::{Error, Row, params};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::database;
+
+
+// Chat ID of the states that apply to every chat
+pub const GLOBAL: i64 = 0;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Plugin {
+    // Plugin name, like `start`
+    pub name: String,
+    // Telegram chat ID the state applies to, `GLOBAL` for every chat
+    pub chat_id: i64,
+    // Whether the plugin handles updates
+    pub enabled: bool,
+}
+
+impl Plugin {
+    // Create the `plugins` table
+    pub fn create() -> Result<(), Error> {
+        let dbc = database::connect().unwrap();
+        let conn = dbc.get_conn();
+
+        let sql = "
+        CREATE TABLE IF NOT EXISTS plugins (
+                name    TEXT NOT NULL,
+                chat_id INTEGER NOT NULL DEFAULT 0,
+                enabled BOOLEAN NOT NULL,
+                PRIMARY KEY (name, chat_id)
+        )
+        ";
+
+        conn.execute(sql, [])?;
+
+        Ok(())
+    }
+
+    // Enable or disable a `plugin` in a chat, or in every chat with `GLOBAL`
+    pub fn set(name: &str, chat_id: i64, enabled: bool) -> Result<(), Error> {
+        let dbc = database::connect().unwrap();
+        let conn = dbc.get_conn();
+
+        let sql = "
+        INSERT INTO plugins (name, chat_id, enabled) VALUES (?, ?, ?)
+        ON CONFLICT (name, chat_id) DO UPDATE SET enabled = excluded.enabled
+        ";
+
+        conn.execute(sql, params![name, chat_id, enabled])?;
+
+        Ok(())
+    }
+
+    // Get every `plugin` state
+    pub fn all() -> Result<Vec<Self>, Error> {
+        let dbc = database::connect().unwrap();
+        let conn = dbc.get_conn();
+
+        let sql = "
+        SELECT name, chat_id, enabled FROM plugins
+        ";
+
+        let mut statement = conn.prepare(sql)?;
+        let rows = statement.query_map([], Self::from_row)?;
+
+        rows.collect()
+    }
+
+    // Save the `plugin` state as is, replacing the existing one of the same chat
+    pub fn save(&self) -> Result<(), Error> {
+        let dbc = database::connect().unwrap();
+        let conn = dbc.get_conn();
+
+        let sql = "
+        INSERT OR REPLACE INTO plugins (name, chat_id, enabled) VALUES (?, ?, ?)
+        ";
+
+        conn.execute(sql, params![self.name, self.chat_id, self.enabled])?;
+
+        Ok(())
+    }
+
+    // Forget the state of a `plugin` in a chat
+    pub fn delete(name: &str, chat_id: i64) -> Result<(), Error> {
+        let dbc = database::connect().unwrap();
+        let conn = dbc.get_conn();
+
+        let sql = "
+        DELETE FROM plugins WHERE name = ? AND chat_id = ?
+        ";
+
+        conn.execute(sql, params![name, chat_id])?;
+
+        Ok(())
+    }
+
+    // Move the states of a chat to another one, replacing its own
+    pub fn move_chat(from: i64, to: i64) -> Result<(), Error> {
+        let dbc = database::connect().unwrap();
+        let conn = dbc.get_conn();
+
+        let sql = "
+        UPDATE OR REPLACE plugins SET chat_id = ? WHERE chat_id = ?
+        ";
+
+        conn.execute(sql, params![to, from])?;
+
+        Ok(())
+    }
+
+    fn from_row(row: &Row) -> Result<Self, Error> {
+        Ok(Self {
+            name: row.get(0)?,
+            chat_id: row.get(1)?,
+            enabled: row.get(2)?,
+        })
+    }
+}
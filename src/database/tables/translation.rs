@@ -0,0 +1,165 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+#![allow(dead_code)]
+#![allow(unused_must_use)]
+
+use rusqlite::{Error, Row, params};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::database;
+
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Translation {
+    pub id: i64,
+    // Locale code, like `pt-rBR`
+    pub locale: String,
+    // Key of the text, like `texts.start`
+    pub key: String,
+    pub text: String,
+    // Telegram ID of who submitted it
+    pub user_id: i64,
+    // Whether an owner approved it, only the approved ones are used
+    pub approved: bool,
+    pub created_at: i64,
+}
+
+impl Translation {
+    // Create the `translations` table
+    pub fn create() -> Result<(), Error> {
+        let dbc = database::connect().unwrap();
+        let conn = dbc.get_conn();
+
+        let sql = "
+        CREATE TABLE IF NOT EXISTS translations (
+                id         INTEGER PRIMARY KEY AUTOINCREMENT,
+                locale     TEXT NOT NULL,
+                key        TEXT NOT NULL,
+                text       TEXT NOT NULL,
+                user_id    INTEGER NOT NULL,
+                approved   BOOLEAN NOT NULL DEFAULT 0,
+                created_at INTEGER NOT NULL
+        )
+        ";
+
+        conn.execute(sql, [])?;
+
+        Ok(())
+    }
+
+    // Save a `translation` waiting for an owner, returns its ID
+    pub fn submit(locale: &str, key: &str, text: &str, user_id: i64) -> Result<i64, Error> {
+        let dbc = database::connect().unwrap();
+        let conn = dbc.get_conn();
+
+        let sql = "
+        INSERT INTO translations (locale, key, text, user_id, created_at) VALUES (?, ?, ?, ?, ?)
+        ";
+
+        conn.execute(sql, params![locale, key, text, user_id, database::timestamp()])?;
+
+        Ok(conn.last_insert_rowid())
+    }
+
+    // Get a `translation` by its ID
+    pub fn get(id: i64) -> Result<Self, Error> {
+        let dbc = database::connect().unwrap();
+        let conn = dbc.get_conn();
+
+        let sql = "
+        SELECT id, locale, key, text, user_id, approved, created_at FROM translations WHERE id = ?
+        ";
+
+        conn.query_row(sql, params![id], Self::from_row)
+    }
+
+    // Get the `translations` waiting for an owner, the oldest first
+    pub fn pending() -> Result<Vec<Self>, Error> {
+        Self::select("WHERE approved = 0 ORDER BY id")
+    }
+
+    // Get the approved `translations`
+    pub fn approved() -> Result<Vec<Self>, Error> {
+        Self::select("WHERE approved = 1 ORDER BY locale, key")
+    }
+
+    // Approve a `translation`, replacing the one approved before for the same text
+    pub fn approve(id: i64) -> Result<(), Error> {
+        let dbc = database::connect().unwrap();
+        let conn = dbc.get_conn();
+
+        let sql = "
+        DELETE FROM translations WHERE approved = 1 AND id != ?1
+        AND locale = (SELECT locale FROM translations WHERE id = ?1)
+        AND key = (SELECT key FROM translations WHERE id = ?1)
+        ";
+        conn.execute(sql, params![id])?;
+
+        let sql = "
+        UPDATE translations SET approved = 1 WHERE id = ?
+        ";
+        conn.execute(sql, params![id])?;
+
+        Ok(())
+    }
+
+    // Get every `translation`, the oldest first
+    pub fn all() -> Result<Vec<Self>, Error> {
+        Self::select("ORDER BY id")
+    }
+
+    // Save the `translation` as is, replacing any existing one with the same id
+    pub fn save(&self) -> Result<(), Error> {
+        let dbc = database::connect().unwrap();
+        let conn = dbc.get_conn();
+
+        let sql = "
+        INSERT OR REPLACE INTO translations (id, locale, key, text, user_id, approved, created_at) VALUES (?, ?, ?, ?, ?, ?, ?)
+        ";
+
+        conn.execute(sql, params![self.id, self.locale, self.key, self.text, self.user_id, self.approved, self.created_at])?;
+
+        Ok(())
+    }
+
+    // Forget a `translation`, used to reject it
+    pub fn delete(id: i64) -> Result<(), Error> {
+        let dbc = database::connect().unwrap();
+        let conn = dbc.get_conn();
+
+        let sql = "
+        DELETE FROM translations WHERE id = ?
+        ";
+
+        conn.execute(sql, params![id])?;
+
+        Ok(())
+    }
+
+    fn select(filter: &str) -> Result<Vec<Self>, Error> {
+        let dbc = database::connect().unwrap();
+        let conn = dbc.get_conn();
+
+        let sql = format!("
+        SELECT id, locale, key, text, user_id, approved, created_at FROM translations {}
+        ", filter);
+
+        let mut statement = conn.prepare(&sql)?;
+        let rows = statement.query_map([], Self::from_row)?;
+
+        rows.collect()
+    }
+
+    fn from_row(row: &Row) -> Result<Self, Error> {
+        Ok(Self {
+            id: row.get(0)?,
+            locale: row.get(1)?,
+            key: row.get(2)?,
+            text: row.get(3)?,
+            user_id: row.get(4)?,
+            approved: row.get(5)?,
+            created_at: row.get(6)?,
+        })
+    }
+}
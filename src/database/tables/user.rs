@@ -4,12 +4,14 @@
 #![allow(dead_code)]
 #![allow(unused_must_use)]
 
-use rusqlite::{Error, params};
+use grammers_session::{PackedChat, PackedType};
+use rusqlite::{Error, Row, params};
+use serde_derive::{Deserialize, Serialize};
 
 use crate::database;
 
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct User {
     // Telegram user ID
     pub id: i64,
@@ -17,6 +19,24 @@ pub struct User {
     pub name: String,
     // Bot user language
     pub language: String,
+    // Telegram user username
+    pub username: Option<String>,
+    // When the user was first seen, UNIX timestamp
+    pub first_seen: i64,
+    // When the user was last seen, UNIX timestamp
+    pub last_seen: i64,
+    // Telegram access hash, needed to message the user first
+    pub access_hash: Option<i64>,
+    // Whether books in every language are shown, after the ones in the languages of the user
+    #[serde(default = "default_show_all_languages")]
+    pub show_all_languages: bool,
+    // Other languages the user reads books in, after `language`, like `en` or `es`
+    #[serde(default)]
+    pub secondary_languages: Vec<String>,
+}
+
+fn default_show_all_languages() -> bool {
+    true
 }
 
 impl User {
@@ -29,12 +49,24 @@ impl User {
         CREATE TABLE IF NOT EXISTS users (
                 id         INTEGER PRIMARY KEY,
                 name       TEXT NOT NULL,
-                language   VARCHAR(6) NOT NULL DEFAULT \"en-GB\"
+                language   VARCHAR(6) NOT NULL DEFAULT \"en-GB\",
+                username   TEXT,
+                first_seen INTEGER NOT NULL DEFAULT 0,
+                last_seen  INTEGER NOT NULL DEFAULT 0,
+                access_hash INTEGER
         )
         ";
 
         conn.execute(sql, []);
 
+        // Columns added after the first release
+        dbc.add_column("users", "username", "TEXT")?;
+        dbc.add_column("users", "first_seen", "INTEGER NOT NULL DEFAULT 0")?;
+        dbc.add_column("users", "last_seen", "INTEGER NOT NULL DEFAULT 0")?;
+        dbc.add_column("users", "access_hash", "INTEGER")?;
+        dbc.add_column("users", "show_all_languages", "BOOLEAN NOT NULL DEFAULT 1")?;
+        dbc.add_column("users", "secondary_languages", "TEXT NOT NULL DEFAULT \"\"")?;
+
         Ok(())
     }
 
@@ -42,12 +74,75 @@ impl User {
     pub fn register(id: i64, name: String, language: Option<&str>) -> Result<(), Error> {
         let dbc = database::connect().unwrap();
         let conn = dbc.get_conn();
+        let now = database::timestamp();
+
+        let sql = "
+        INSERT INTO users (id, name, language, first_seen, last_seen) VALUES (?, ?, COALESCE(?, \"en-GB\"), ?, ?)
+        ";
+
+        conn.execute(sql, params![id, name, language, now, now]);
+
+        Ok(())
+    }
+
+    // Register a `user` or refresh its profile if it already exists
+    pub fn upsert(id: i64, name: &str, username: Option<&str>, language: Option<&str>, access_hash: Option<i64>) -> Result<(), Error> {
+        let dbc = database::connect().unwrap();
+        let conn = dbc.get_conn();
+        let now = database::timestamp();
+
+        let sql = "
+        INSERT INTO users (id, name, username, language, first_seen, last_seen, access_hash) VALUES (?, ?, ?, COALESCE(?, \"en-GB\"), ?, ?, ?)
+        ON CONFLICT (id) DO UPDATE SET
+                name = excluded.name,
+                username = excluded.username,
+                last_seen = excluded.last_seen,
+                access_hash = COALESCE(excluded.access_hash, users.access_hash)
+        ";
+
+        conn.execute(sql, params![id, name, username, language, now, now, access_hash])?;
+
+        Ok(())
+    }
+
+    // Change whether a `user` sees books in every language
+    pub fn set_show_all_languages(id: i64, show_all_languages: bool) -> Result<(), Error> {
+        let dbc = database::connect().unwrap();
+        let conn = dbc.get_conn();
+
+        let sql = "
+        UPDATE users SET show_all_languages = ? WHERE id = ?
+        ";
+
+        conn.execute(sql, params![show_all_languages, id])?;
+
+        Ok(())
+    }
+
+    // Change the other languages a `user` reads books in
+    pub fn set_secondary_languages(id: i64, secondary_languages: &[String]) -> Result<(), Error> {
+        let dbc = database::connect().unwrap();
+        let conn = dbc.get_conn();
+
+        let sql = "
+        UPDATE users SET secondary_languages = ? WHERE id = ?
+        ";
+
+        conn.execute(sql, params![secondary_languages.join(","), id])?;
+
+        Ok(())
+    }
+
+    // Update when a `user` was last seen
+    pub fn set_last_seen(id: i64, last_seen: i64) -> Result<(), Error> {
+        let dbc = database::connect().unwrap();
+        let conn = dbc.get_conn();
 
         let sql = "
-        INSERT INTO users (id, name, language) VALUES (?, ?, ?)
+        UPDATE users SET last_seen = ? WHERE id = ?
         ";
 
-        conn.execute(sql, params![id, name, language]);
+        conn.execute(sql, params![last_seen, id])?;
 
         Ok(())
     }
@@ -58,16 +153,39 @@ impl User {
         let conn = dbc.get_conn();
 
         let sql = "
-        SELECT * FROM users WHERE id = ?
+        SELECT id, name, language, username, first_seen, last_seen, access_hash, show_all_languages, secondary_languages FROM users WHERE id = ?
         ";
 
-        conn.query_row(sql, params![id], |row| {
-            Ok(Self {
-                id: row.get(0).unwrap(),
-                name: row.get(1).unwrap(),
-                language: row.get(2).unwrap(),
-            })
-        })
+        conn.query_row(sql, params![id], Self::from_row)
+    }
+
+    // Get every `user`
+    pub fn all() -> Result<Vec<Self>, Error> {
+        let dbc = database::connect().unwrap();
+        let conn = dbc.get_conn();
+
+        let sql = "
+        SELECT id, name, language, username, first_seen, last_seen, access_hash, show_all_languages, secondary_languages FROM users
+        ";
+
+        let mut statement = conn.prepare(sql)?;
+        let rows = statement.query_map([], Self::from_row)?;
+
+        rows.collect()
+    }
+
+    // Save the `user` as is, replacing any existing one with the same id
+    pub fn save(&self) -> Result<(), Error> {
+        let dbc = database::connect().unwrap();
+        let conn = dbc.get_conn();
+
+        let sql = "
+        INSERT OR REPLACE INTO users (id, name, language, username, first_seen, last_seen, access_hash, show_all_languages, secondary_languages) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ";
+
+        conn.execute(sql, params![self.id, self.name, self.language, self.username, self.first_seen, self.last_seen, self.access_hash, self.show_all_languages, self.secondary_languages.join(",")])?;
+
+        Ok(())
     }
 
     // Delete a `user` by id
@@ -83,4 +201,31 @@ impl User {
 
         Ok(())
     }
+
+    // Get what is needed to message the `user`
+    pub fn pack(&self) -> PackedChat {
+        PackedChat {
+            ty: PackedType::User,
+            id: self.id,
+            access_hash: self.access_hash,
+        }
+    }
+
+    fn from_row(row: &Row) -> Result<Self, Error> {
+        Ok(Self {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            language: row.get(2)?,
+            username: row.get(3)?,
+            first_seen: row.get(4)?,
+            last_seen: row.get(5)?,
+            access_hash: row.get(6)?,
+            show_all_languages: row.get(7)?,
+            secondary_languages: row.get::<_, String>(8)?
+                .split(',')
+                .filter(|language| !language.is_empty())
+                .map(String::from)
+                .collect(),
+        })
+    }
 }
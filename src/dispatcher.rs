@@ -0,0 +1,231 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use log::{debug, error, warn};
+use tokio::sync::{Semaphore, mpsc};
+use tokio::{task, time};
+
+use crate::api::{self, Api, CallbackAnswer, Update};
+use crate::config;
+use crate::handler::{self, Handler};
+use crate::logging;
+use crate::utils::RateLimiter;
+
+
+// How long a chat queue waits for new updates before being closed
+const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+// A callback press, identified by the chat, the user, the message and the button data
+type PressKey = (i64, i64, Option<i32>, Vec<u8>);
+
+struct Job {
+    id: u64,
+    update: Update,
+    // Set for callbacks, so repeated presses are dropped while this one is pending
+    press: Option<PressKey>,
+}
+
+// What every update needs to be handled, shared by all tasks
+struct Shared {
+    client: Arc<dyn Api>,
+    handler_list: Vec<Handler<'static>>,
+    prefixes: Vec<String>,
+    me: api::User,
+    // Limits how many updates are handled at the same time
+    semaphore: Semaphore,
+    // Queues of the chats with updates being handled, so they are handled in order
+    queues: Mutex<HashMap<i64, mpsc::Sender<Job>>>,
+    user_limiter: RateLimiter<i64>,
+    chat_limiter: RateLimiter<i64>,
+    // Callback presses queued or being handled
+    presses: Mutex<HashSet<PressKey>>,
+}
+
+// Hands the updates to the handlers, in order within a chat and concurrently across chats
+pub struct Dispatcher {
+    shared: Arc<Shared>,
+    // Every task holds a sender, so `recv` returns once all of them are dropped
+    tracker: mpsc::Sender<()>,
+    tasks_done: mpsc::Receiver<()>,
+    update_id: u64,
+    // Updates each chat queue holds
+    queue_size: usize,
+}
+
+impl Dispatcher {
+    pub fn new(client: Arc<dyn Api>, handler_list: Vec<Handler<'static>>, prefixes: Vec<String>, me: api::User, limits: &config::Limits) -> Self {
+        let (tracker, tasks_done) = mpsc::channel(1);
+
+        Self {
+            shared: Arc::new(Shared {
+                client: client,
+                handler_list: handler_list,
+                prefixes: prefixes,
+                me: me,
+                semaphore: Semaphore::new(limits.concurrency.max(1)),
+                queues: Mutex::new(HashMap::new()),
+                user_limiter: RateLimiter::new(limits.user_burst, limits.user_rate),
+                chat_limiter: RateLimiter::new(limits.chat_burst, limits.chat_rate),
+                presses: Mutex::new(HashSet::new()),
+            }),
+            tracker: tracker,
+            tasks_done: tasks_done,
+            update_id: 0,
+            queue_size: limits.queue.max(1),
+        }
+    }
+
+    // Queue an update to be handled
+    pub fn dispatch(&mut self, update: Update) {
+        self.update_id += 1;
+
+        let chat_id = handler::get_chat_id(&update);
+        let sender_id = handler::get_sender_id(&update);
+
+        let press = match &update {
+            Update::CallbackQuery(callback) => Some((callback.chat.id(), callback.sender.id(), callback.message_id, callback.data.clone())),
+            _ => None,
+        };
+        if let Some(press) = press.as_ref() {
+            if !self.shared.presses.lock().unwrap().insert(press.clone()) {
+                debug!("Dropping the update {}, the same button press is pending", self.update_id);
+                self.drop_update(update);
+
+                return;
+            }
+        }
+
+        // The tokens are only taken once both limits allow the update
+        let allowed = sender_id.map(|id| self.shared.user_limiter.peek(id)).unwrap_or(true)
+            && chat_id.map(|id| self.shared.chat_limiter.peek(id)).unwrap_or(true);
+        if !allowed {
+            debug!("Dropping the update {}, rate limit reached", self.update_id);
+            if let Some(press) = press.as_ref() {
+                self.shared.presses.lock().unwrap().remove(press);
+            }
+            self.drop_update(update);
+
+            return;
+        }
+        if let Some(id) = sender_id {
+            self.shared.user_limiter.check(id);
+        }
+        if let Some(id) = chat_id {
+            self.shared.chat_limiter.check(id);
+        }
+
+        let job = Job {
+            id: self.update_id,
+            update: update,
+            press: press,
+        };
+
+        let chat_id = match chat_id {
+            Some(chat_id) => chat_id,
+            None => {
+                // Updates out of a chat have no order to keep
+                let shared = self.shared.clone();
+                let tracker = self.tracker.clone();
+
+                task::spawn(async move {
+                    run(&shared, job).await;
+                    drop(tracker);
+                });
+
+                return;
+            }
+        };
+
+        let mut queues = self.shared.queues.lock().unwrap();
+        let job = match queues.get(&chat_id) {
+            Some(queue) => match queue.try_send(job) {
+                Ok(_) => return,
+                Err(mpsc::error::TrySendError::Full(job)) => {
+                    warn!("Dropping the update {}, the queue of the chat {} is full", job.id, chat_id);
+                    if let Some(press) = job.press.as_ref() {
+                        self.shared.presses.lock().unwrap().remove(press);
+                    }
+                    self.drop_update(job.update);
+
+                    return;
+                }
+                // The worker is gone, start a new one below
+                Err(mpsc::error::TrySendError::Closed(job)) => job,
+            },
+            None => job,
+        };
+
+        let (queue, receiver) = mpsc::channel(self.queue_size);
+        queue.try_send(job).ok();
+        queues.insert(chat_id, queue);
+
+        task::spawn(work(self.shared.clone(), chat_id, receiver, self.tracker.clone()));
+    }
+
+    // Stop accepting updates and wait up to `timeout` for the queued ones, returns `false` on timeout
+    pub async fn shutdown(mut self, timeout: Duration) -> bool {
+        self.shared.queues.lock().unwrap().clear();
+        drop(self.tracker);
+
+        time::timeout(timeout, self.tasks_done.recv()).await.is_ok()
+    }
+
+    // Discard an update, stopping the loading animation if it is a callback
+    fn drop_update(&self, update: Update) {
+        if let Update::CallbackQuery(callback) = update {
+            let client = self.shared.client.clone();
+            let tracker = self.tracker.clone();
+
+            task::spawn(async move {
+                client.answer_callback(&callback, CallbackAnswer::default()).await.ok();
+                drop(tracker);
+            });
+        }
+    }
+}
+
+// Handle the updates of a chat, one at a time, until it stays idle
+async fn work(shared: Arc<Shared>, chat_id: i64, mut receiver: mpsc::Receiver<Job>, tracker: mpsc::Sender<()>) {
+    loop {
+        let job = match time::timeout(IDLE_TIMEOUT, receiver.recv()).await {
+            Ok(Some(job)) => job,
+            Ok(None) => break,
+            Err(_) => {
+                // Only close the queue if nothing arrived while holding the lock `dispatch` uses
+                let mut queues = shared.queues.lock().unwrap();
+                match receiver.try_recv() {
+                    Ok(job) => job,
+                    Err(_) => {
+                        queues.remove(&chat_id);
+                        break;
+                    }
+                }
+            }
+        };
+
+        run(&shared, job).await;
+    }
+
+    drop(tracker);
+}
+
+async fn run(shared: &Shared, job: Job) {
+    let Job { id, update, press } = job;
+
+    let _permit = shared.semaphore.acquire().await;
+    let context = logging::Context::new(id, handler::get_chat_id(&update));
+
+    logging::scope(context, async {
+        if let Err(e) = handler::handle_update(shared.client.as_ref(), update, &shared.handler_list, &shared.prefixes, &shared.me).await {
+            error!("Error handling the update ({}): {}", logging::context().unwrap_or_default(), e);
+        }
+    }).await;
+
+    if let Some(press) = press {
+        shared.presses.lock().unwrap().remove(&press);
+    }
+}
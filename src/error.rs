@@ -0,0 +1,105 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use std::backtrace::Backtrace;
+use std::fmt;
+use std::io;
+
+use grammers_client::InvocationError;
+
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum ErrorKind {
+    // A request to Telegram failed
+    Invocation(InvocationError),
+    // A database operation failed
+    Database(rusqlite::Error),
+    // Reading or writing a file failed
+    Io(io::Error),
+    // Anything else, described by the message
+    Other(String),
+}
+
+// An error returned by a handler, with where it was created
+#[derive(Debug)]
+pub struct Error {
+    kind: ErrorKind,
+    backtrace: Backtrace,
+}
+
+impl Error {
+    pub fn new(kind: ErrorKind) -> Self {
+        Self {
+            kind: kind,
+            backtrace: Backtrace::capture(),
+        }
+    }
+
+    pub fn other<S: Into<String>>(message: S) -> Self {
+        Self::new(ErrorKind::Other(message.into()))
+    }
+
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    // Where the error was created, only captured if `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE` is set
+    pub fn backtrace(&self) -> &Backtrace {
+        &self.backtrace
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            ErrorKind::Invocation(e) => write!(f, "Telegram request failed: {}", e),
+            ErrorKind::Database(e) => write!(f, "Database operation failed: {}", e),
+            ErrorKind::Io(e) => write!(f, "I/O operation failed: {}", e),
+            ErrorKind::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            ErrorKind::Invocation(e) => Some(e),
+            ErrorKind::Database(e) => Some(e),
+            ErrorKind::Io(e) => Some(e),
+            ErrorKind::Other(_) => None,
+        }
+    }
+}
+
+impl From<InvocationError> for Error {
+    fn from(e: InvocationError) -> Self {
+        Self::new(ErrorKind::Invocation(e))
+    }
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(e: rusqlite::Error) -> Self {
+        Self::new(ErrorKind::Database(e))
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Self::new(ErrorKind::Io(e))
+    }
+}
+
+impl From<Box<dyn std::error::Error + Send + Sync>> for Error {
+    fn from(e: Box<dyn std::error::Error + Send + Sync>) -> Self {
+        Self::other(e.to_string())
+    }
+}
+
+// A blocking task panicked or was cancelled
+impl From<tokio::task::JoinError> for Error {
+    fn from(e: tokio::task::JoinError) -> Self {
+        Self::other(e.to_string())
+    }
+}
@@ -4,20 +4,38 @@
 #![allow(dead_code)]
 #![allow(unused_must_use)]
 
+use std::backtrace::BacktraceStatus;
+use std::cmp::Reverse;
 use std::error::Error;
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
 
-use grammers_client::{Client, Update, types};
+use grammers_session::{PackedChat, PackedType};
 use regex::Regex;
-use log::info;
+use log::{error, info, warn};
 
+use crate::api::{self, Api, CallbackAnswer, OutgoingMessage, Update};
+use crate::config;
+use crate::error;
 use crate::handlers;
 use crate::language;
-use crate::database::tables;
+use crate::logging;
+use crate::plugins;
+use crate::database::{presence, tables};
+use crate::utils::{html, text};
 
+pub mod args;
+pub mod conversation;
+pub mod filter;
+mod membership;
+mod replies;
 
-pub type AsyncFunction = for<'a> fn (&'a Data) -> Pin<Box<dyn Future<Output = ()> + Send + 'a>>;
+pub use args::Args;
+pub use filter::Filter;
+
+
+pub type AsyncFunction = for<'a> fn (&'a Data) -> Pin<Box<dyn Future<Output = error::Result<()>> + Send + 'a>>;
 
 #[macro_export]
 macro_rules! dyn_async {(
@@ -38,17 +56,101 @@ macro_rules! dyn_async {(
 )}
 
 pub struct Data<'a> {
-    pub client: &'a Client,
-    pub message: Option<&'a types::Message>,
-    pub callback: Option<&'a types::CallbackQuery>,
+    pub client: &'a dyn Api,
+    pub message: Option<&'a api::Message>,
+    pub callback: Option<&'a api::CallbackQuery>,
     // pub inline: Option<&'a types::InlineQuery>,
     pub request: &'a str,
+    // Whether the message was edited, the handler runs again if it accepts edited messages
+    pub edited: bool,
+    // Characters that start a command
+    pub prefixes: &'a [String],
     pub language: language::I18n<'a>,
-    pub me: &'a types::User,
+    pub me: &'a api::User,
+    // Whether the callback was answered, shared by every handler of the update
+    pub answered: &'a AtomicBool,
+}
+
+impl<'a> Data<'a> {
+    // Read the arguments of the command
+    pub fn args(&self) -> Result<Args, args::Error> {
+        Args::parse(self.request, self.prefixes, self.me.username.as_deref())
+    }
+
+    // Reply to the message, or edit the previous reply if the message was edited, returns the ID of the reply
+    pub async fn reply(&self, reply: OutgoingMessage) -> error::Result<i32> {
+        let message = self.message
+            .ok_or_else(|| error::Error::other("There is no message to reply to"))?;
+        let chat_id = message.chat.id();
+
+        if self.edited {
+            if let Some(reply_id) = replies::get(chat_id, message.id) {
+                self.client.edit_message(message.chat.pack(), reply_id, reply).await?;
+
+                return Ok(reply_id);
+            }
+        }
+
+        let reply_id = self.client.send_message(message.chat.pack(), reply.reply_to(Some(message.id))).await?;
+        replies::remember(chat_id, message.id, reply_id);
+
+        Ok(reply_id)
+    }
+
+    // Tell the user the arguments are wrong and how to use the command
+    pub async fn reply_usage(&self, error: &args::Error, usage: &str) -> error::Result<()> {
+        if self.message.is_none() {
+            return Ok(());
+        }
+
+        self.reply(OutgoingMessage::html(error.localize(self.language, usage))).await?;
+
+        Ok(())
+    }
+
+    // Answer the callback without showing anything, only stops the loading animation
+    pub async fn answer(&self) -> error::Result<()> {
+        self.answer_callback(CallbackAnswer::default()).await
+    }
+
+    // Answer the callback with a toast, a short text shown on top of the chat
+    pub async fn answer_toast(&self, text: &str) -> error::Result<()> {
+        self.answer_callback(CallbackAnswer { text: Some(text.to_string()), ..Default::default() }).await
+    }
+
+    // Answer the callback with an alert, a text the user must dismiss
+    pub async fn answer_alert(&self, text: &str) -> error::Result<()> {
+        self.answer_callback(CallbackAnswer { text: Some(text.to_string()), alert: true, ..Default::default() }).await
+    }
+
+    // Answer the callback opening a link, like `t.me/MyneBot?start=...` to start the bot in private
+    pub async fn answer_url(&self, url: &str) -> error::Result<()> {
+        self.answer_callback(CallbackAnswer { url: Some(url.to_string()), ..Default::default() }).await
+    }
+
+    // Whether the callback was already answered
+    pub fn is_answered(&self) -> bool {
+        self.answered.load(Ordering::SeqCst)
+    }
+
+    async fn answer_callback(&self, answer: CallbackAnswer) -> error::Result<()> {
+        let callback = match self.callback {
+            Some(callback) => callback,
+            None => return Ok(()),
+        };
+
+        // A callback can only be answered once
+        if self.answered.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        self.client.answer_callback(callback, answer).await
+    }
 }
 
 #[derive(Clone)]
 pub struct Handler<'a> {
+    plugin: &'a str,
     update_type: &'a str,
     function: AsyncFunction,
     pattern: &'a str,
@@ -60,6 +162,14 @@ pub struct HandlerOptions<'a> {
     pub is_command: bool,
     pub description: Option<&'a str>,
     pub hide: bool,
+    // Handlers with a higher priority run first, the ones with the same priority run in registration order
+    pub priority: i32,
+    // Don't run the next handlers once this one matches
+    pub stop: bool,
+    // Conditions the update must meet, all of them
+    pub filters: &'a [Filter<'a>],
+    // Run again when the message is edited
+    pub edited: bool,
 }
 
 impl<'a> Default for HandlerOptions<'a> {
@@ -68,6 +178,10 @@ impl<'a> Default for HandlerOptions<'a> {
             is_command: false,
             description: None,
             hide: true,
+            priority: 0,
+            stop: false,
+            filters: &[],
+            edited: false,
         }
     }
 }
@@ -93,6 +207,7 @@ impl<'a> Register<'a> {
 
     pub fn append(mut self, update_type: &'a str, function: AsyncFunction, pattern: &'a str, options: Option<HandlerOptions<'a>>) -> Self {
         let handler = Handler {
+            plugin: self.name,
             update_type: update_type,
             function: function,
             pattern: pattern,
@@ -131,63 +246,259 @@ impl<'a> Register<'a> {
     }
 
     pub fn get_handler_list(self) -> Vec<Handler<'a>> {
+        let name = self.name;
+
         self.handler_list
+            .into_iter()
+            .map(|handler| Handler { plugin: name, ..handler })
+            .collect()
+    }
+}
+
+impl<'a> Handler<'a> {
+    // Name used in the logs, like `about/message/about$`
+    pub fn get_name(&self) -> String {
+        format!("{}/{}/{}", self.plugin, self.update_type, self.pattern)
+    }
+
+    pub fn get_priority(&self) -> i32 {
+        self.options.unwrap_or_default().priority
     }
 }
 
+// Load the handlers of every plugin, the disabled ones are kept so they can be enabled at runtime
 pub fn initialize<'a>(handler_list: &mut Vec<Handler<'a>>) -> Result<(), Box<dyn Error>> {
+    let config = config::get().plugins.clone();
 
-    for plugin_register in vec![
-        handlers::start::initialize(), // start.rs
-        handlers::about::initialize(),  // about.rs
-    ] {
-        if plugin_register.is_enabled() {
-            info!("Loading plugin '{}'", plugin_register.get_name());
+    for name in config.enabled.iter().chain(config.disabled.iter()) {
+        if !handlers::all().iter().any(|plugin_register| plugin_register.get_name() == name) {
+            warn!("Unknown plugin '{}' in the configuration", name);
+        }
+    }
 
-            for plugin_handler in plugin_register.get_handler_list() {
-                handler_list.push(plugin_handler);
-            }
+    for plugin_register in handlers::all() {
+        let name = plugin_register.get_name();
+        let enabled = if config.enabled.iter().any(|plugin| plugin == name) {
+            true
+        } else if config.disabled.iter().any(|plugin| plugin == name) {
+            false
+        } else {
+            plugin_register.is_enabled()
+        };
+
+        if enabled {
+            info!("Loading plugin '{}'", name);
         } else {
-            info!("Plugin '{}' is disabled, passing...", plugin_register.get_name());
+            info!("Plugin '{}' is disabled, passing...", name);
         }
+
+        plugins::register(name, enabled);
+        handler_list.extend(plugin_register.get_handler_list());
     }
 
+    handler_list.sort_by_key(|handler| Reverse(handler.get_priority()));
+
+    // The states set at runtime come before the configuration
+    plugins::load()?;
+    language::overrides::load()?;
+
     Ok(())
 }
 
-pub async fn handle_update<'a>(mut client: Client, update: Update, handler_list: Vec<Handler<'a>>, prefixes: Vec<String>, me: types::User) -> Result<(), Box<dyn Error>> {
+// Record the chat, and the sender on groups, then get its language
+async fn update_chat(client: &dyn Api, chat: &api::Chat, sender: Option<&api::Chat>) -> Option<language::I18n<'static>> {
+    let features = config::get().features;
+
+    match chat {
+        api::Chat::User(user) => {
+            update_user(user, features.refresh_profiles);
+
+            Some(language::from_user(user.id))
+        }
+        api::Chat::Group(group) => {
+            if let Some(api::Chat::User(user)) = sender {
+                update_user(user, features.refresh_profiles);
+            }
+
+            if !features.refresh_profiles {
+                if tables::Group::get(group.id).is_err() {
+                    tables::Group::register(group.id, &group.title);
+                }
+
+                return Some(language::from_group(group.id));
+            }
+
+            let fingerprint = format!("{}\0{}", group.title, group.username.as_deref().unwrap_or_default());
+            if presence::touch(presence::Kind::Group, group.id, &fingerprint) {
+                let packed = chat.pack();
+                let chat_type = match packed.ty {
+                    PackedType::Megagroup => "supergroup",
+                    PackedType::Gigagroup => "gigagroup",
+                    _ => "group",
+                };
+                let members_count = if features.members_count {
+                    client.members_count(packed)
+                        .await
+                        .ok()
+                        .flatten()
+                } else {
+                    None
+                };
+
+                if let Err(e) = tables::Group::upsert(group.id, &group.title, group.username.as_deref(), chat_type, members_count, packed.access_hash) {
+                    presence::forget(group.id);
+                    error!("Failed to save the group {}: {}", group.id, e);
+                }
+            }
+
+            Some(language::from_group(group.id))
+        }
+        api::Chat::Channel(channel) => {
+            if !features.refresh_profiles {
+                if tables::Channel::get(channel.id).is_err() {
+                    tables::Channel::register(channel.id, &channel.title);
+                }
+
+                return Some(language::from_channel(channel.id));
+            }
+
+            let fingerprint = format!("{}\0{}", channel.title, channel.username.as_deref().unwrap_or_default());
+            if presence::touch(presence::Kind::Channel, channel.id, &fingerprint) {
+                if let Err(e) = tables::Channel::upsert(channel.id, &channel.title, channel.username.as_deref(), channel.packed.access_hash) {
+                    presence::forget(channel.id);
+                    error!("Failed to save the channel {}: {}", channel.id, e);
+                }
+            }
+
+            Some(language::from_channel(channel.id))
+        }
+    }
+}
+
+fn update_user(user: &api::User, refresh: bool) {
+    if !refresh {
+        if tables::User::get(user.id).is_err() {
+            tables::User::register(user.id, user.full_name(), user.lang_code.as_deref());
+        }
+
+        return;
+    }
+
+    let fingerprint = format!("{}\0{}", user.full_name(), user.username.as_deref().unwrap_or_default());
+    if presence::touch(presence::Kind::User, user.id, &fingerprint) {
+        if let Err(e) = tables::User::upsert(user.id, &user.full_name(), user.username.as_deref(), user.lang_code.as_deref(), user.packed.access_hash) {
+            presence::forget(user.id);
+            error!("Failed to save the user {}: {}", user.id, e);
+        }
+    }
+}
+
+// Log a handler error, tell the user something went wrong and report it to the log chat
+async fn report_error(data: &Data<'_>, error: error::Error) {
+    let context = logging::context().unwrap_or_default();
+    error!("Handler failed ({}): {}", context, error);
+
+    let text = data.language.get_text("errors.generic", vec![]);
+    let result = match (data.message, data.callback) {
+        (_, Some(_)) if !data.is_answered() => data.answer_alert(text.trim()).await,
+        (Some(message), _) => {
+            data.client.send_message(message.chat.pack(), OutgoingMessage::html(text.as_str()).reply_to(Some(message.id)))
+                .await
+                .map(|_| ())
+        }
+        (None, Some(callback)) => {
+            data.client.send_message(callback.chat.pack(), OutgoingMessage::html(text.as_str()))
+                .await
+                .map(|_| ())
+        }
+        (None, None) => Ok(()),
+    };
+    if let Err(e) = result {
+        error!("Failed to tell the user about the error ({}): {}", context, e);
+    }
+
+    let log_chat = config::get().admins.log_chat;
+    if log_chat == 0 {
+        return;
+    }
+
+    let chat = match find_chat(log_chat) {
+        Some(chat) => chat,
+        None => {
+            warn!("The log chat {} was not seen yet, the error was not reported", log_chat);
+            return;
+        }
+    };
+
+    let mut report = format!("<b>Handler failed</b>\n<code>{}</code>\n\n<pre>{}</pre>", html::escape(&context.to_string()), html::escape(&error.to_string()));
+    if error.backtrace().status() == BacktraceStatus::Captured {
+        report.push_str(&format!("\n\n<pre>{}</pre>", html::escape(&error.backtrace().to_string())));
+    }
+
+    if let Err(e) = data.client.send_message(chat, OutgoingMessage::html(text::truncate(&report, text::MESSAGE_LIMIT))).await {
+        error!("Failed to report the error to the log chat ({}): {}", context, e);
+    }
+}
+
+// Whether the message was sent by one of the owners
+pub fn is_owner(message: &api::Message) -> bool {
+    match message.sender.as_ref() {
+        Some(api::Chat::User(user)) => config::is_owner(user.id),
+        _ => false,
+    }
+}
+
+// Find a chat the bot saw before by its Bot API ID, negative for groups and prefixed by -100 for supergroups
+// and channels
+fn find_chat(id: i64) -> Option<PackedChat> {
+    if id > 0 {
+        tables::User::get(id).ok().map(|user| user.pack())
+    } else if id < -1_000_000_000_000 {
+        let id = -id - 1_000_000_000_000;
+        tables::Group::get(id).ok().map(|group| group.pack())
+            .or_else(|| tables::Channel::get(id).ok().map(|channel| channel.pack()))
+    } else {
+        tables::Group::get(-id).ok().map(|group| group.pack())
+    }
+}
+
+// Get the ID of the chat an update happened in
+pub fn get_chat_id(update: &Update) -> Option<i64> {
+    update.chat().map(|chat| chat.id())
+}
+
+// Get the ID of who sent an update
+pub fn get_sender_id(update: &Update) -> Option<i64> {
+    update.sender().map(|sender| sender.id())
+}
+
+pub async fn handle_update<'a>(client: &dyn Api, update: Update, handler_list: &[Handler<'a>], prefixes: &[String], me: &api::User) -> Result<(), Box<dyn Error>> {
     let mut lang = language::I18n::default();
+    let answered = AtomicBool::new(false);
 
     match update {
-        Update::NewMessage(ref message) if !message.outgoing() => {
-
-            let chat = message.chat();
-            match chat {
-                types::Chat::User(user) => {
-                    match tables::User::get(user.id()) {
-                        Ok(_) => {}
-                        Err(_) => {
-                            tables::User::register(user.id(), user.full_name(), user.lang_code());
-                        }
-                    }
+        Update::NewMessage(ref message) | Update::MessageEdited(ref message) if !message.outgoing => {
+            let edited = matches!(update, Update::MessageEdited(_));
 
-                    lang = language::from_user(user.id());
-                }
-                types::Chat::Group(group) => {
-                    match tables::Group::get(group.id()) {
-                        Ok(_) => {}
-                        Err(_) => {
-                            tables::Group::register(group.id(), group.title());
-                        }
-                    }
+            if let Some(language) = update_chat(client, &message.chat, message.sender.as_ref()).await {
+                lang = language;
+            }
 
-                    lang = language::from_group(group.id());
+            // Service messages have no text for the handlers
+            if let Some(action) = message.action.as_ref() {
+                if !edited {
+                    membership::handle_action(client, message, action, lang, prefixes, me).await;
                 }
-                types::Chat::Channel(_) => {}
+
+                return Ok(());
             }
 
+            let mut filters = filter::Context::new(client, &message.chat, message.sender.as_ref(), Some(message), lang.get_language_code());
+            let chat_id = Some(message.chat.id());
             let message_handlers = handler_list.iter()
-                .filter(|handler| handler.update_type == "message");
+                .filter(|handler| handler.update_type == "message")
+                .filter(|handler| !edited || handler.options.unwrap_or_default().edited)
+                .filter(|handler| plugins::is_enabled(handler.plugin, chat_id));
             for handler in message_handlers {
                 let options = handler.options.unwrap_or_default();
                 let function = handler.function;
@@ -211,9 +522,13 @@ pub async fn handle_update<'a>(mut client: Client, update: Update, handler_list:
                             .join(" "));
                     }
 
-                    pattern.push_str(format!("(?:@{})?", me.username().unwrap()).as_str());
+                    // Telegram usernames are case-insensitive, like the parsed arguments
+                    pattern.push_str(format!("(?:@(?i:{}))?", regex::escape(me.username.as_deref().unwrap_or_default())).as_str());
 
-                    pattern.insert_str(0, format!("^[{}]", prefixes.join("")).as_str());
+                    let prefixes: String = prefixes.iter()
+                        .map(|prefix| regex::escape(prefix))
+                        .collect();
+                    pattern.insert_str(0, format!("^[{}]", prefixes).as_str());
 
                     let pattern_parts = &pattern_splitted[1..];
                     for part in pattern_parts {
@@ -226,71 +541,86 @@ pub async fn handle_update<'a>(mut client: Client, update: Update, handler_list:
                 }
 
                 let pattern = pattern.as_str();
-                let request = message.text();
+                let request = message.text.as_str();
 
                 let re = Regex::new(pattern).unwrap();
-                if re.is_match(request) {
+                if re.is_match(request) && filters.check(options.filters).await {
+                    logging::set_handler(handler.get_name());
+
                     let data = Data {
-                        client: &mut client,
+                        client: client,
                         message: Some(message),
                         callback: None,
                         request: request,
+                        edited: edited,
+                        prefixes: prefixes,
                         language: lang,
-                        me: &me,
+                        me: me,
+                        answered: &answered,
                     };
-                    function(&data).await;
-                }
-            }
-        }
-        Update::CallbackQuery(ref callback) => {
-            let chat = callback.chat();
-            match chat {
-                types::Chat::User(user) => {
-                    match tables::User::get(user.id()) {
-                        Ok(_) => {}
-                        Err(_) => {
-                            tables::User::register(user.id(), user.full_name(), user.lang_code());
-                        }
+                    if let Err(e) = function(&data).await {
+                        report_error(&data, e).await;
                     }
 
-                    lang = language::from_user(user.id());
-                }
-                types::Chat::Group(group) => {
-                    match tables::Group::get(group.id()) {
-                        Ok(_) => {}
-                        Err(_) => {
-                            tables::Group::register(group.id(), group.title());
-                        }
+                    if options.stop {
+                        break;
                     }
-
-                    lang = language::from_group(group.id());
                 }
-                types::Chat::Channel(_) => {}
+            }
+        }
+        Update::CallbackQuery(ref callback) => {
+            if let Some(language) = update_chat(client, &callback.chat, Some(&callback.sender)).await {
+                lang = language;
             }
 
+            let mut filters = filter::Context::new(client, &callback.chat, Some(&callback.sender), None, lang.get_language_code());
+            let chat_id = Some(callback.chat.id());
             let callback_handlers = handler_list.iter()
-                .filter(|handler| handler.update_type == "callback");
+                .filter(|handler| handler.update_type == "callback")
+                .filter(|handler| plugins::is_enabled(handler.plugin, chat_id));
             for handler in callback_handlers {
+                let options = handler.options.unwrap_or_default();
                 let function = handler.function;
                 let pattern = handler.pattern;
 
-                let data = callback.data();
-                let request = std::str::from_utf8(data).unwrap();
+                let request = match std::str::from_utf8(&callback.data) {
+                    Ok(request) => request,
+                    Err(_) => continue,
+                };
 
                 let re = Regex::new(pattern).unwrap();
-                if re.is_match(request) {
+                if re.is_match(request) && filters.check(options.filters).await {
+                    logging::set_handler(handler.get_name());
+
                     let data = Data {
-                        client: &mut client,
+                        client: client,
                         message: None,
                         callback: Some(callback),
                         request: request,
+                        edited: false,
+                        prefixes: prefixes,
                         language: lang,
-                        me: &me,
+                        me: me,
+                        answered: &answered,
                     };
-                    function(&data).await;
+                    if let Err(e) = function(&data).await {
+                        report_error(&data, e).await;
+                    }
+
+                    if options.stop {
+                        break;
+                    }
+                }
+            }
+
+            // Stop the loading animation if no handler answered
+            if !answered.load(Ordering::SeqCst) {
+                if let Err(e) = client.answer_callback(callback, CallbackAnswer::default()).await {
+                    error!("Failed to answer the callback: {}", e);
                 }
             }
         }
+        Update::ParticipantLeft { chat_id, user_id } if user_id == me.id => membership::left(chat_id),
         _ => {}
     }
 
@@ -0,0 +1,206 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::language::I18n;
+
+
+// Why the arguments of a command could not be read
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    // A required argument was not given
+    Missing(String),
+    // An argument could not be converted, like a word where a number was expected
+    Invalid {
+        name: String,
+        value: String,
+    },
+    // A quoted argument was not closed
+    UnclosedQuote,
+}
+
+impl Error {
+    // Explain the error to the user, followed by how to use the command
+    pub fn localize(&self, lang: I18n, usage: &str) -> String {
+        match self {
+            Self::Missing(name) => lang.get_text("errors.missing_argument", vec![("name", name.as_str()), ("usage", usage)]),
+            Self::Invalid { name, value } => lang.get_text("errors.invalid_argument", vec![("name", name.as_str()), ("value", value.as_str()), ("usage", usage)]),
+            Self::UnclosedQuote => lang.get_text("errors.unclosed_quote", vec![("usage", usage)]),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Missing(name) => write!(f, "Missing the `{}` argument", name),
+            Self::Invalid { name, value } => write!(f, "`{}` is not a valid `{}`", value, name),
+            Self::UnclosedQuote => write!(f, "A quote was not closed"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+// The arguments of a command, like `/search --lang pt --format epub "o alienista"`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Args {
+    // The command without the prefix and the bot username, empty if the text is not a command
+    command: String,
+    // Arguments that are not flags, in order
+    positional: Vec<String>,
+    // Flags by name, `--lang pt` and `--lang=pt` give `pt`, a flag followed by nothing or another flag gives `None`
+    flags: BTreeMap<String, Option<String>>,
+}
+
+impl Args {
+    // Read the arguments of a text, `--` stops reading flags and quoted words are never flags
+    pub fn parse(text: &str, prefixes: &[String], username: Option<&str>) -> Result<Self, Error> {
+        let mut tokens = tokenize(text)?.into_iter().peekable();
+        let mut args = Self::default();
+
+        if let Some((first, false)) = tokens.peek() {
+            if let Some(command) = strip_command(first, prefixes, username) {
+                args.command = command;
+                tokens.next();
+            }
+        }
+
+        let mut flags_ended = false;
+        while let Some((token, quoted)) = tokens.next() {
+            if quoted || flags_ended || !token.starts_with("--") {
+                args.positional.push(token);
+                continue;
+            }
+            if token == "--" {
+                flags_ended = true;
+                continue;
+            }
+
+            let flag = token.trim_start_matches("--");
+            if let Some((name, value)) = flag.split_once('=') {
+                args.flags.insert(name.to_string(), Some(value.to_string()));
+                continue;
+            }
+
+            let value = match tokens.peek() {
+                Some((next, next_quoted)) if *next_quoted || !next.starts_with("--") => tokens.next().map(|(value, _)| value),
+                _ => None,
+            };
+            args.flags.insert(flag.to_string(), value);
+        }
+
+        Ok(args)
+    }
+
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    // How many arguments that are not flags were given
+    pub fn len(&self) -> usize {
+        self.positional.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positional.is_empty()
+    }
+
+    // Get a required argument, `name` is shown to the user if it is missing or invalid
+    pub fn get<T: FromStr>(&self, index: usize, name: &str) -> Result<T, Error> {
+        self.get_opt(index, name)?
+            .ok_or_else(|| Error::Missing(name.to_string()))
+    }
+
+    // Get an optional argument
+    pub fn get_opt<T: FromStr>(&self, index: usize, name: &str) -> Result<Option<T>, Error> {
+        self.positional.get(index)
+            .map(|value| convert(name, value))
+            .transpose()
+    }
+
+    // Get the arguments from `index` on, joined by spaces, like the query of a search
+    pub fn rest(&self, index: usize) -> String {
+        self.positional.get(index..)
+            .map(|rest| rest.join(" "))
+            .unwrap_or_default()
+    }
+
+    // Whether a flag was given, with or without a value
+    pub fn has_flag(&self, name: &str) -> bool {
+        self.flags.contains_key(name)
+    }
+
+    // Get the value of a flag, an error if it was given without one
+    pub fn flag<T: FromStr>(&self, name: &str) -> Result<Option<T>, Error> {
+        match self.flags.get(name) {
+            Some(Some(value)) => convert(name, value).map(Some),
+            Some(None) => Err(Error::Missing(format!("--{}", name))),
+            None => Ok(None),
+        }
+    }
+}
+
+fn convert<T: FromStr>(name: &str, value: &str) -> Result<T, Error> {
+    value.parse().map_err(|_| Error::Invalid {
+        name: name.to_string(),
+        value: value.to_string(),
+    })
+}
+
+// Remove the prefix and the bot username of a command, `None` if it is not a command
+fn strip_command(token: &str, prefixes: &[String], username: Option<&str>) -> Option<String> {
+    let prefix = prefixes.iter().find(|prefix| token.starts_with(prefix.as_str()))?;
+    let command = &token[prefix.len()..];
+
+    match (command.split_once('@'), username) {
+        (Some((command, mention)), Some(username)) if mention.eq_ignore_ascii_case(username) => Some(command.to_string()),
+        (Some(_), _) => None,
+        (None, _) => Some(command.to_string()),
+    }
+}
+
+// Split a text by whitespace, keeping quoted parts together, returns each word and whether it was quoted
+fn tokenize(text: &str) -> Result<Vec<(String, bool)>, Error> {
+    let mut tokens = Vec::new();
+    let mut token = String::new();
+    let mut quoted = false;
+    let mut quote = None;
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        match quote {
+            Some(close) if c == close => quote = None,
+            Some(_) if c == '\\' => {
+                if let Some(escaped) = chars.next() {
+                    token.push(escaped);
+                }
+            }
+            Some(_) => token.push(c),
+            // Only quotes starting a word, so `don't` is kept as is
+            None if (c == '"' || c == '\'' || c == '“') && token.is_empty() && !quoted => {
+                quote = Some(if c == '“' { '”' } else { c });
+                quoted = true;
+            }
+            None if c.is_whitespace() => {
+                if !token.is_empty() || quoted {
+                    tokens.push((std::mem::take(&mut token), quoted));
+                }
+                quoted = false;
+            }
+            None => token.push(c),
+        }
+    }
+
+    if quote.is_some() {
+        return Err(Error::UnclosedQuote);
+    }
+    if !token.is_empty() || quoted {
+        tokens.push((token, quoted));
+    }
+
+    Ok(tokens)
+}
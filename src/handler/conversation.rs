@@ -0,0 +1,63 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use lazy_static::lazy_static;
+
+use crate::config;
+
+
+lazy_static! {
+    static ref CONVERSATIONS: Mutex<HashMap<i64, Conversation>> = Mutex::new(HashMap::new());
+}
+
+// A question the bot asked a user, whose next message is the answer
+struct Conversation {
+    // Name of the conversation, like `translate`
+    name: String,
+    // What the handler needs to know to handle the answer
+    state: String,
+    started_at: Instant,
+}
+
+// Forget the conversations nobody answered in time, the users may never come back to them
+fn expire(conversations: &mut HashMap<i64, Conversation>) {
+    let timeout = Duration::from_secs(config::get().limits.conversation_timeout);
+    conversations.retain(|_, conversation| conversation.started_at.elapsed() < timeout);
+}
+
+// Wait for the answer of a user, replacing the conversation they were having
+pub fn start(user_id: i64, name: &str, state: &str) {
+    let mut conversations = CONVERSATIONS.lock().unwrap();
+    expire(&mut conversations);
+
+    conversations.insert(user_id, Conversation {
+        name: name.to_string(),
+        state: state.to_string(),
+        started_at: Instant::now(),
+    });
+}
+
+// Get the state of the conversation `name` a user is having
+pub fn get(user_id: i64, name: &str) -> Option<String> {
+    let mut conversations = CONVERSATIONS.lock().unwrap();
+    expire(&mut conversations);
+
+    match conversations.get(&user_id) {
+        Some(conversation) if conversation.name == name => Some(conversation.state.clone()),
+        _ => None,
+    }
+}
+
+// Get how many users the bot is waiting for an answer from
+pub fn count() -> usize {
+    CONVERSATIONS.lock().unwrap().len()
+}
+
+// Stop waiting for the answer of a user
+pub fn finish(user_id: i64) {
+    CONVERSATIONS.lock().unwrap().remove(&user_id);
+}
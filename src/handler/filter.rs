@@ -0,0 +1,143 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use log::warn;
+
+use crate::api::{self, Api};
+use crate::config;
+use crate::handler::conversation;
+
+
+// A condition an update must meet to be handled, composed with `All`, `Any` and `Not`
+#[derive(Clone, Copy, Debug)]
+pub enum Filter<'a> {
+    // Sent in a private chat
+    Private,
+    // Sent in a group
+    Group,
+    // Posted in a channel
+    Channel,
+    // Sent by an admin of the group or by an owner of the bot
+    Admin,
+    // The message has a document attached
+    HasDocument,
+    // The message replies to one sent by the bot
+    ReplyToBot,
+    // The chat uses this language, like `pt-rBR`
+    Language(&'a str),
+    // The sender is answering a question of the conversation with this name
+    Conversation(&'a str),
+    All(&'a [Filter<'a>]),
+    Any(&'a [Filter<'a>]),
+    Not(&'a Filter<'a>),
+}
+
+impl<'a> Filter<'a> {
+    fn needs_admin(&self) -> bool {
+        match self {
+            Self::Admin => true,
+            Self::All(filters) | Self::Any(filters) => filters.iter().any(Filter::needs_admin),
+            Self::Not(filter) => filter.needs_admin(),
+            _ => false,
+        }
+    }
+
+    fn needs_reply(&self) -> bool {
+        match self {
+            Self::ReplyToBot => true,
+            Self::All(filters) | Self::Any(filters) => filters.iter().any(Filter::needs_reply),
+            Self::Not(filter) => filter.needs_reply(),
+            _ => false,
+        }
+    }
+
+    fn matches(&self, context: &Context) -> bool {
+        match self {
+            Self::Private => matches!(context.chat, api::Chat::User(_)),
+            Self::Group => matches!(context.chat, api::Chat::Group(_)),
+            Self::Channel => matches!(context.chat, api::Chat::Channel(_)),
+            Self::Admin => context.admin.unwrap_or(false),
+            Self::HasDocument => context.message.map(|message| message.document.is_some()).unwrap_or(false),
+            Self::ReplyToBot => context.reply_to_bot.unwrap_or(false),
+            Self::Language(code) => context.language == *code,
+            Self::Conversation(name) => context.sender.map(|sender| conversation::get(sender.id(), name).is_some()).unwrap_or(false),
+            Self::All(filters) => filters.iter().all(|filter| filter.matches(context)),
+            Self::Any(filters) => filters.iter().any(|filter| filter.matches(context)),
+            Self::Not(filter) => !filter.matches(context),
+        }
+    }
+}
+
+// What the filters of an update are checked against, the parts that need requests are fetched once, when needed
+pub struct Context<'a> {
+    client: &'a dyn Api,
+    chat: &'a api::Chat,
+    sender: Option<&'a api::Chat>,
+    message: Option<&'a api::Message>,
+    language: &'a str,
+    admin: Option<bool>,
+    reply_to_bot: Option<bool>,
+}
+
+impl<'a> Context<'a> {
+    pub fn new(client: &'a dyn Api, chat: &'a api::Chat, sender: Option<&'a api::Chat>, message: Option<&'a api::Message>, language: &'a str) -> Self {
+        Self {
+            client: client,
+            chat: chat,
+            sender: sender,
+            message: message,
+            language: language,
+            admin: None,
+            reply_to_bot: None,
+        }
+    }
+
+    // Whether the update meets every filter
+    pub async fn check(&mut self, filters: &[Filter<'_>]) -> bool {
+        if self.admin.is_none() && filters.iter().any(Filter::needs_admin) {
+            self.admin = Some(self.is_admin().await);
+        }
+        if self.reply_to_bot.is_none() && filters.iter().any(Filter::needs_reply) {
+            self.reply_to_bot = Some(self.is_reply_to_bot().await);
+        }
+
+        filters.iter().all(|filter| filter.matches(self))
+    }
+
+    async fn is_admin(&self) -> bool {
+        let user = match self.sender {
+            Some(api::Chat::User(user)) => user,
+            _ => return false,
+        };
+
+        if config::is_owner(user.id) {
+            return true;
+        }
+        if !matches!(self.chat, api::Chat::Group(_)) {
+            return false;
+        }
+
+        match self.client.is_admin(self.chat.pack(), user.packed).await {
+            Ok(admin) => admin,
+            Err(e) => {
+                warn!("Failed to check if {} is an admin of {}: {}", user.id, self.chat.id(), e);
+                false
+            }
+        }
+    }
+
+    async fn is_reply_to_bot(&self) -> bool {
+        let message = match self.message {
+            Some(message) if message.reply_to_message_id.is_some() => message,
+            _ => return false,
+        };
+
+        match self.client.reply_to_message(message).await {
+            Ok(reply) => reply.map(|reply| reply.outgoing).unwrap_or(false),
+            Err(e) => {
+                warn!("Failed to get the message {} replies to: {}", message.id, e);
+                false
+            }
+        }
+    }
+}
@@ -0,0 +1,80 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use log::{error, info};
+use rusqlite::Error;
+
+use crate::api::{self, Action, Api, OutgoingMessage};
+use crate::database::{presence, tables};
+use crate::language::I18n;
+use crate::plugins;
+use crate::utils::html;
+
+
+// Keep track of the groups the bot is in, from their service messages
+pub async fn handle_action(client: &dyn Api, message: &api::Message, action: &Action, lang: I18n<'_>, prefixes: &[String], me: &api::User) {
+    let group = match &message.chat {
+        api::Chat::Group(group) => group,
+        _ => return,
+    };
+
+    match action {
+        Action::MembersAdded(users) if users.contains(&me.id) => {
+            info!("Added to the group {}", group.id);
+
+            // The profile may not be written again if the bot was removed and added back quickly
+            if let Err(e) = tables::Group::set_active(group.id, true) {
+                error!("Failed to mark the group {} as active: {}", group.id, e);
+            }
+
+            let prefixes = prefixes.iter()
+                .map(|prefix| format!("<code>{}</code>", html::escape(prefix)))
+                .collect::<Vec<String>>()
+                .join(" ");
+            let text = lang.get_text("texts.welcome", vec![
+                ("title", group.title.as_str()),
+                ("prefixes", prefixes.as_str()),
+                ("bot_username", me.username.as_deref().unwrap_or_default()),
+            ]);
+
+            if let Err(e) = client.send_message(message.chat.pack(), OutgoingMessage::html(text)).await {
+                error!("Failed to welcome the group {}: {}", group.id, e);
+            }
+        }
+        Action::MemberLeft(user_id) if *user_id == me.id => left(group.id),
+        Action::MigratedTo(supergroup_id) => {
+            info!("The group {} became the supergroup {}", group.id, supergroup_id);
+
+            if let Err(e) = migrate(group.id, *supergroup_id) {
+                error!("Failed to move the settings of the group {} to the supergroup {}: {}", group.id, supergroup_id, e);
+            }
+        }
+        _ => {}
+    }
+}
+
+// Keep the settings of a group that became a supergroup, the old group will never be seen again
+fn migrate(group_id: i64, supergroup_id: i64) -> Result<(), Error> {
+    if let Ok(old) = tables::Group::get(group_id) {
+        if tables::Group::get(supergroup_id).is_err() {
+            tables::Group::register(supergroup_id, &old.title)?;
+        }
+        tables::Group::set_language(supergroup_id, &old.language)?;
+    }
+    plugins::move_chat(group_id, supergroup_id)?;
+
+    presence::forget(group_id);
+    tables::Group::delete(group_id)?;
+
+    Ok(())
+}
+
+// Mark a group as one the bot is no longer in
+pub fn left(group_id: i64) {
+    info!("Removed from the group {}", group_id);
+
+    presence::forget(group_id);
+    if let Err(e) = tables::Group::set_active(group_id, false) {
+        error!("Failed to mark the group {} as inactive: {}", group_id, e);
+    }
+}
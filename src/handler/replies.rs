@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+
+
+// How many replies are remembered, the oldest ones are forgotten first
+const CAPACITY: usize = 4096;
+
+lazy_static! {
+    static ref REPLIES: Mutex<Replies> = Mutex::new(Replies::default());
+}
+
+// The messages the bot sent as replies, so they are edited when the message they reply to is
+#[derive(Default)]
+struct Replies {
+    ids: HashMap<(i64, i32), i32>,
+    order: VecDeque<(i64, i32)>,
+}
+
+// Remember that `reply_id` replies to the message `message_id` of a chat
+pub fn remember(chat_id: i64, message_id: i32, reply_id: i32) {
+    let mut replies = REPLIES.lock().unwrap();
+
+    if replies.ids.insert((chat_id, message_id), reply_id).is_none() {
+        replies.order.push_back((chat_id, message_id));
+    }
+
+    while replies.order.len() > CAPACITY {
+        if let Some(key) = replies.order.pop_front() {
+            replies.ids.remove(&key);
+        }
+    }
+}
+
+// Get the reply the bot sent to the message `message_id` of a chat
+pub fn get(chat_id: i64, message_id: i32) -> Option<i32> {
+    REPLIES.lock().unwrap().ids.get(&(chat_id, message_id)).copied()
+}
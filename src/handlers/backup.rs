@@ -0,0 +1,81 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use std::fs;
+use std::path::Path;
+
+use log::warn;
+use tokio::task;
+
+use crate::api::OutgoingMessage;
+use crate::config;
+use crate::dyn_async;
+use crate::database::{self, backup};
+use crate::error::Result;
+use crate::handler::{self, Data, HandlerOptions, Register};
+
+
+#[macro_rules_attribute(dyn_async!)]
+async fn backup_message<'fut>(data: &'fut Data) -> Result<()> {
+    let client = data.client;
+    let message = data.message.unwrap();
+    let lang = data.language;
+
+    if !handler::is_owner(message) {
+        return Ok(());
+    }
+
+    // SQLite copies the database page by page, away from the workers handling the updates
+    let directory = config::get().storage.backups.clone();
+    let path = task::spawn_blocking(move || backup::backup(&directory)).await??;
+    let file_name = path.file_name().unwrap().to_string_lossy().to_string();
+
+    let text = OutgoingMessage::html(lang.get_text("texts.backup_done", vec![("file_name", file_name.as_str())]))
+        .document(path)
+        .reply_to(Some(message.id));
+    client.send_message(message.chat.pack(), text).await?;
+
+    Ok(())
+}
+
+#[macro_rules_attribute(dyn_async!)]
+async fn export_message<'fut>(data: &'fut Data) -> Result<()> {
+    let client = data.client;
+    let message = data.message.unwrap();
+    let lang = data.language;
+
+    if !handler::is_owner(message) {
+        return Ok(());
+    }
+
+    let directory = config::get().storage.backups.clone();
+    let path = Path::new(&directory).join(format!("myne_books-{}.json", database::timestamp()));
+    let export_path = path.to_string_lossy().to_string();
+    let dump = task::spawn_blocking(move || backup::export(&export_path)).await??;
+    let users = lang.get_plural("counts.users", dump.users.len() as i64, vec![]);
+    let groups = lang.get_plural("counts.groups", dump.groups.len() as i64, vec![]);
+    let channels = lang.get_plural("counts.channels", dump.channels.len() as i64, vec![]);
+
+    let text = OutgoingMessage::html(lang.get_text("texts.export_done", vec![("users", users.as_str()), ("groups", groups.as_str()), ("channels", channels.as_str())]))
+        .document(path.clone())
+        .reply_to(Some(message.id));
+    let result = client.send_message(message.chat.pack(), text).await;
+
+    // The export is only kept in the chat, unlike the backups
+    if let Err(e) = fs::remove_file(&path) {
+        warn!("Failed to delete the export {}: {}", path.display(), e);
+    }
+    result?;
+
+    Ok(())
+}
+
+
+pub fn initialize<'a>() -> Register<'a> {
+    Register::new()
+        .set_name("backup")
+        .set_enabled(true)
+        .append("message", backup_message, "backup$", Some(HandlerOptions { is_command: true, description: Some("Backup the database"), ..Default::default() }))
+        .append("message", export_message, "export$", Some(HandlerOptions { is_command: true, description: Some("Export the database as JSON"), ..Default::default() }))
+        .build()
+}
@@ -0,0 +1,131 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use crate::api::OutgoingMessage;
+use crate::database::tables;
+use crate::dyn_async;
+use crate::error::Result;
+use crate::handler::{Data, Filter, HandlerOptions, Register};
+use crate::language::{self, format, preference::Preference, I18n};
+use crate::utils::{Button, Keyboard};
+
+
+const USAGE: &str = "/languages [language...|none]";
+
+#[macro_rules_attribute(dyn_async!)]
+async fn languages_message<'fut>(data: &'fut Data) -> Result<()> {
+    let message = data.message.unwrap();
+    let lang = data.language;
+    let user_id = message.chat.id();
+
+    let args = match data.args() {
+        Ok(args) => args,
+        Err(e) => return data.reply_usage(&e, USAGE).await,
+    };
+
+    // The languages given replace the secondary ones, `none` removes them
+    if !args.is_empty() {
+        let mut languages = Vec::new();
+        for code in args.rest(0).split_whitespace() {
+            if code.eq_ignore_ascii_case("none") {
+                continue;
+            }
+            if !language::is_code(code) {
+                data.reply(OutgoingMessage::html(lang.get_text("errors.invalid_locale", vec![("locale", code)]))).await?;
+                return Ok(());
+            }
+
+            languages.push(code.to_string());
+        }
+
+        tables::User::set_secondary_languages(user_id, &languages)?;
+    }
+
+    data.reply(get_languages_message(lang, &tables::User::get(user_id)?)?).await?;
+
+    Ok(())
+}
+
+#[macro_rules_attribute(dyn_async!)]
+async fn languages_callback<'fut>(data: &'fut Data) -> Result<()> {
+    let client = data.client;
+    let callback = data.callback.unwrap();
+    let lang = data.language;
+    let user_id = callback.sender.id();
+
+    let user = tables::User::get(user_id)?;
+    match data.request.strip_prefix("languages toggle ") {
+        // The language of the user is always the first one, only the secondary ones are added or removed
+        Some(code) if format::base(&user.language).eq_ignore_ascii_case(code) => return Ok(()),
+        Some(code) => {
+            let mut languages = user.secondary_languages.clone();
+            match languages.iter().position(|language| format::base(language).eq_ignore_ascii_case(code)) {
+                Some(position) => {
+                    languages.remove(position);
+                }
+                None => languages.push(code.to_string()),
+            }
+
+            tables::User::set_secondary_languages(user_id, &languages)?;
+        }
+        None => tables::User::set_show_all_languages(user_id, !user.show_all_languages)?,
+    }
+
+    client.edit_callback_message(callback, get_languages_message(lang, &tables::User::get(user_id)?)?).await?;
+
+    Ok(())
+}
+
+fn get_languages_message(lang: I18n, user: &tables::User) -> Result<OutgoingMessage> {
+    let preference = Preference::from_user(user);
+    let languages = preference.languages()
+        .iter()
+        .map(|language| format!("<code>{}</code>", language))
+        .collect::<Vec<String>>()
+        .join(", ");
+    let (key, button) = if preference.show_all() {
+        ("texts.languages_all", "buttons.show_my_languages")
+    } else {
+        ("texts.languages_mine", "buttons.show_all_languages")
+    };
+
+    // The languages the bot has locales for, the ones of the user first, even if the others are hidden
+    let mut codes: Vec<String> = Vec::new();
+    for locale in language::locales() {
+        let code = format::base(locale).to_lowercase();
+        if !codes.contains(&code) {
+            codes.push(code);
+        }
+    }
+    let codes = Preference::new(&user.language, &user.secondary_languages, true).sort(codes, String::as_str);
+    let buttons = codes.iter()
+        .map(|code| {
+            let text = if preference.languages().contains(code) {
+                format!("✓ {}", code)
+            } else {
+                code.clone()
+            };
+
+            Button::callback(text, format!("languages toggle {}", code))
+        });
+
+    Ok(OutgoingMessage::html(lang.get_text(key, vec![("languages", languages.as_str())]))
+        .reply_markup(&Keyboard::builder()
+            .columns(4)
+            .buttons(buttons)
+            .row()
+            .button(Button::callback(lang.get_text(button, vec![]), "languages all"))
+            .build()?))
+}
+
+
+pub fn initialize<'a>() -> Register<'a> {
+    Register::new()
+        .set_name("languages")
+        .set_enabled(true)
+        .append("message", languages_message, "languages$", Some(HandlerOptions { is_command: true, description: Some("Choose the languages of the books"), filters: &[Filter::Private], ..Default::default() }))
+        .append("message", languages_message, "languages .+$", Some(HandlerOptions { is_command: true, filters: &[Filter::Private], ..Default::default() }))
+        .append("callback", languages_callback, "^languages all$", Some(HandlerOptions { filters: &[Filter::Private], ..Default::default() }))
+        .append("callback", languages_callback, "^languages toggle [a-z]{2,3}$", Some(HandlerOptions { filters: &[Filter::Private], ..Default::default() }))
+        .build()
+}
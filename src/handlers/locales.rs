@@ -0,0 +1,58 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use crate::api::{self, OutgoingMessage};
+use crate::database::tables;
+use crate::dyn_async;
+use crate::error::Result;
+use crate::handler::{self, Data, HandlerOptions, Register};
+use crate::language::{preference::Preference, runtime};
+use crate::utils::html;
+
+
+#[macro_rules_attribute(dyn_async!)]
+async fn reload_message<'fut>(data: &'fut Data) -> Result<()> {
+    let message = data.message.unwrap();
+    let lang = data.language;
+
+    if !handler::is_owner(message) {
+        return Ok(());
+    }
+
+    let directory = match runtime::directory() {
+        Some(directory) => directory.display().to_string(),
+        None => {
+            data.reply(OutgoingMessage::html(lang.get_text("errors.locales_disabled", vec![]))).await?;
+            return Ok(());
+        }
+    };
+
+    let text = match runtime::reload() {
+        Ok(locales) => {
+            // The languages of the owner first, like every other listing
+            let locales = match message.sender.as_ref().map(api::Chat::id).map(tables::User::get) {
+                Some(Ok(user)) => Preference::new(&user.language, &user.secondary_languages, true).sort(locales, |locale| *locale),
+                _ => locales,
+            };
+            let locales = locales.iter()
+                .map(|locale| format!("<code>{}</code>", html::escape(locale)))
+                .collect::<Vec<String>>()
+                .join(", ");
+
+            lang.get_text("texts.locales_reloaded", vec![("directory", directory.as_str()), ("locales", locales.as_str())])
+        }
+        Err(e) => lang.get_text("errors.locales_reload_failed", vec![("directory", directory.as_str()), ("error", e.to_string().as_str())]),
+    };
+    data.reply(OutgoingMessage::html(text)).await?;
+
+    Ok(())
+}
+
+
+pub fn initialize<'a>() -> Register<'a> {
+    Register::new()
+        .set_name("locales")
+        .set_enabled(true)
+        .append("message", reload_message, "reload_locales$", Some(HandlerOptions { is_command: true, description: Some("Reload the locales directory"), ..Default::default() }))
+        .build()
+}
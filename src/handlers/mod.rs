@@ -1,5 +1,28 @@
 // SPDX-License-Identifier: MIT
 // Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
 
+use crate::handler::Register;
+
 pub mod start;
 pub mod about;
+pub mod backup;
+pub mod plugins;
+pub mod locales;
+pub mod translate;
+pub mod languages;
+pub mod reader;
+
+
+// Every plugin, in the order their handlers run
+pub fn all<'a>() -> Vec<Register<'a>> {
+    vec![
+        start::initialize(),
+        about::initialize(),
+        backup::initialize(),
+        plugins::initialize(),
+        locales::initialize(),
+        translate::initialize(),
+        languages::initialize(),
+        reader::initialize(),
+    ]
+}
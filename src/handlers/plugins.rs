@@ -0,0 +1,97 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use crate::api::{self, OutgoingMessage};
+use crate::dyn_async;
+use crate::error::Result;
+use crate::handler::{self, Data, HandlerOptions, Register};
+use crate::plugins;
+
+
+#[macro_rules_attribute(dyn_async!)]
+async fn plugins_message<'fut>(data: &'fut Data) -> Result<()> {
+    let message = data.message.unwrap();
+    let lang = data.language;
+
+    if !handler::is_owner(message) {
+        return Ok(());
+    }
+
+    let chat_id = message.chat.id();
+    let list = plugins::names()
+        .iter()
+        .map(|name| {
+            let flag = if plugins::is_enabled(name, Some(chat_id)) { "✅" } else { "❌" };
+            format!("{} <code>{}</code>", flag, name)
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    data.reply(OutgoingMessage::html(lang.get_text("texts.plugins", vec![("plugins", list.as_str())]))).await?;
+
+    Ok(())
+}
+
+#[macro_rules_attribute(dyn_async!)]
+async fn enable_message<'fut>(data: &'fut Data) -> Result<()> {
+    toggle(data, true).await
+}
+
+#[macro_rules_attribute(dyn_async!)]
+async fn disable_message<'fut>(data: &'fut Data) -> Result<()> {
+    toggle(data, false).await
+}
+
+// Enable or disable the plugin named after the command, like `/disable about global`
+async fn toggle(data: &Data<'_>, enabled: bool) -> Result<()> {
+    let message = data.message.unwrap();
+    let lang = data.language;
+
+    if !handler::is_owner(message) {
+        return Ok(());
+    }
+
+    let usage = format!("/{} name [global]", if enabled { "enable" } else { "disable" });
+    let (name, scope) = match data.args().and_then(|args| Ok((args.get::<String>(0, "name")?, args.get_opt::<String>(1, "global")?))) {
+        Ok(args) => args,
+        Err(e) => return data.reply_usage(&e, &usage).await,
+    };
+    let name = name.as_str();
+
+    // Private chats have no one else to change the plugins for
+    let global = scope.as_deref() == Some("global") || matches!(message.chat, api::Chat::User(_));
+
+    let text = if !plugins::exists(name) {
+        lang.get_text("errors.unknown_plugin", vec![("name", name)])
+    } else if !enabled && plugins::PROTECTED.contains(&name) {
+        lang.get_text("errors.protected_plugin", vec![("name", name)])
+    } else {
+        let chat_id = if global { None } else { Some(message.chat.id()) };
+        plugins::set(name, chat_id, enabled)?;
+
+        let key = match (enabled, global) {
+            (true, true) => "texts.plugin_enabled_global",
+            (true, false) => "texts.plugin_enabled",
+            (false, true) => "texts.plugin_disabled_global",
+            (false, false) => "texts.plugin_disabled",
+        };
+        lang.get_text(key, vec![("name", name)])
+    };
+
+    data.reply(OutgoingMessage::html(text)).await?;
+
+    Ok(())
+}
+
+
+pub fn initialize<'a>() -> Register<'a> {
+    Register::new()
+        .set_name("plugins")
+        .set_enabled(true)
+        .append("message", plugins_message, "plugins$", Some(HandlerOptions { is_command: true, description: Some("List the plugins"), ..Default::default() }))
+        .append("message", enable_message, "enable$", Some(HandlerOptions { is_command: true, description: Some("Enable a plugin"), ..Default::default() }))
+        .append("message", enable_message, "enable .+$", Some(HandlerOptions { is_command: true, ..Default::default() }))
+        .append("message", disable_message, "disable$", Some(HandlerOptions { is_command: true, description: Some("Disable a plugin"), ..Default::default() }))
+        .append("message", disable_message, "disable .+$", Some(HandlerOptions { is_command: true, ..Default::default() }))
+        .build()
+}
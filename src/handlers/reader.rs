@@ -0,0 +1,114 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use crate::api::OutgoingMessage;
+use crate::dyn_async;
+use crate::error::Result;
+use crate::handler::{Data, HandlerOptions, Register};
+use crate::language::I18n;
+use crate::reader::{self, toc::{self, Contents, Navigation}};
+use crate::utils::{Keyboard, Paginator, Payload};
+
+
+const USAGE: &str = "/read <book> [page]";
+
+#[macro_rules_attribute(dyn_async!)]
+async fn read_message<'fut>(data: &'fut Data) -> Result<()> {
+    let lang = data.language;
+
+    let args = match data.args() {
+        Ok(args) => args,
+        Err(e) => return data.reply_usage(&e, USAGE).await,
+    };
+    let book = match args.get::<i64>(0, "book") {
+        Ok(book) => book,
+        Err(e) => return data.reply_usage(&e, USAGE).await,
+    };
+    let page = match args.get_opt::<usize>(1, "page") {
+        Ok(page) => page.unwrap_or(1),
+        Err(e) => return data.reply_usage(&e, USAGE).await,
+    };
+
+    let contents = match open(book)? {
+        Some(contents) => contents,
+        None => {
+            data.reply(OutgoingMessage::html(lang.get_text("errors.book_not_found", vec![]))).await?;
+            return Ok(());
+        }
+    };
+    data.reply(get_page_message(lang, book, page, &contents)?).await?;
+
+    Ok(())
+}
+
+// The buttons of the reader and of the contents, see `Navigation`
+#[macro_rules_attribute(dyn_async!)]
+async fn read_callback<'fut>(data: &'fut Data) -> Result<()> {
+    let client = data.client;
+    let callback = data.callback.unwrap();
+    let lang = data.language;
+
+    let navigation = match Navigation::decode(data.request) {
+        Some(navigation) => navigation,
+        None => return Ok(()),
+    };
+    let book = match navigation {
+        Navigation::Read { book, .. } | Navigation::Contents { book, .. } => book,
+    };
+
+    // The book may have been removed since the message was sent
+    let contents = match open(book)? {
+        Some(contents) => contents,
+        None => return data.answer_alert(lang.get_text("errors.book_not_found", vec![]).trim()).await,
+    };
+
+    let message = match navigation {
+        Navigation::Read { book, page } => get_page_message(lang, book, page, &contents)?,
+        Navigation::Contents { book, reading, page } => OutgoingMessage::html(lang.get_text("texts.reader_contents", vec![]))
+            .reply_markup(&contents.keyboard(lang, book, reading, page)?),
+    };
+    client.edit_callback_message(callback, message).await?;
+
+    Ok(())
+}
+
+// Read the contents of a book, `None` if there is no such book
+fn open(book: i64) -> Result<Option<Contents>> {
+    match reader::find(book) {
+        Some(path) => Ok(Some(toc::from_file(&path)?)),
+        None => Ok(None),
+    }
+}
+
+// Show a page of a book with the chapter it is in, the buttons to turn the pages and the one opening the contents
+fn get_page_message(lang: I18n, book: i64, page: usize, contents: &Contents) -> Result<OutgoingMessage> {
+    let paginator = Paginator::new(format!("read {}", book), page, contents.last_page());
+    let page = paginator.page();
+
+    let entries = contents.entries();
+    let chapter = contents.current(page)
+        .map(|index| entries[index].title)
+        .unwrap_or("—");
+    let number = page.to_string();
+    let pages = paginator.pages().to_string();
+
+    let mut builder = Keyboard::builder().paginator(&paginator);
+    if !contents.is_empty() {
+        builder = builder.button(contents.button(lang, book, page));
+    }
+
+    Ok(OutgoingMessage::html(lang.get_text("texts.reader_page", vec![("chapter", chapter), ("page", number.as_str()), ("pages", pages.as_str())]))
+        .reply_markup(&builder.build()?))
+}
+
+
+pub fn initialize<'a>() -> Register<'a> {
+    Register::new()
+        .set_name("reader")
+        .set_enabled(true)
+        .append("message", read_message, "read$", Some(HandlerOptions { is_command: true, description: Some("Read a book"), ..Default::default() }))
+        .append("message", read_message, "read .+$", Some(HandlerOptions { is_command: true, ..Default::default() }))
+        .append("callback", read_callback, r"^read \d+ \d+$", None)
+        .append("callback", read_callback, r"^contents \d+ \d+ \d+$", None)
+        .build()
+}
@@ -0,0 +1,327 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use std::fs;
+use std::path::Path;
+
+use crate::api::{self, OutgoingMessage};
+use crate::config;
+use crate::database::{self, tables};
+use crate::dyn_async;
+use crate::error::Result;
+use crate::handler::{self, conversation, Data, Filter, HandlerOptions, Register};
+use crate::language::{self, format, loader, overrides, I18n};
+use crate::utils::{self, Button, Keyboard, Payload};
+
+
+// Name of the conversation of the translators, its state is the locale and the key being translated
+const CONVERSATION: &str = "translate";
+
+// What an owner did with a submitted translation, by its ID
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Review {
+    Approve(i64),
+    Reject(i64),
+}
+
+impl Payload for Review {
+    fn encode(&self) -> String {
+        match self {
+            Self::Approve(id) => format!("translation approve {}", id),
+            Self::Reject(id) => format!("translation reject {}", id),
+        }
+    }
+
+    fn decode(data: &str) -> Option<Self> {
+        let mut words = data.split_whitespace();
+        if words.next()? != "translation" {
+            return None;
+        }
+
+        match (words.next()?, words.next()?.parse().ok()?) {
+            ("approve", id) => Some(Self::Approve(id)),
+            ("reject", id) => Some(Self::Reject(id)),
+            _ => None,
+        }
+    }
+}
+
+#[macro_rules_attribute(dyn_async!)]
+async fn translate_message<'fut>(data: &'fut Data) -> Result<()> {
+    let message = data.message.unwrap();
+    let lang = data.language;
+
+    let user = match message.sender.as_ref() {
+        Some(api::Chat::User(user)) if config::is_translator(user.id) => user,
+        _ => return Ok(()),
+    };
+
+    let args = match data.args() {
+        Ok(args) => args,
+        Err(e) => return data.reply_usage(&e, "/translate [language]").await,
+    };
+    // The language of the user if none was given, even if the bot does not have it yet
+    let code = match args.get_opt::<String>(0, "language") {
+        Ok(Some(code)) => code,
+        Ok(None) => tables::User::get(user.id)?.language,
+        Err(e) => return data.reply_usage(&e, "/translate [language]").await,
+    };
+
+    if !language::is_code(&code) {
+        data.reply(OutgoingMessage::html(lang.get_text("errors.invalid_locale", vec![("locale", code.as_str())]))).await?;
+        return Ok(());
+    }
+    let locale = language::find(&code).map(String::from).unwrap_or(code);
+    if format::base(&locale).eq_ignore_ascii_case(format::base(language::LOCALES[0])) {
+        data.reply(OutgoingMessage::html(lang.get_text("errors.translate_source", vec![("locale", locale.as_str())]))).await?;
+        return Ok(());
+    }
+
+    data.reply(next_key_message(lang, user.id, &locale, None)?).await?;
+
+    Ok(())
+}
+
+// The answer of a translator, the translation of the key they were asked for
+#[macro_rules_attribute(dyn_async!)]
+async fn answer_message<'fut>(data: &'fut Data) -> Result<()> {
+    let message = data.message.unwrap();
+    let lang = data.language;
+
+    // Commands are not answers, like /translate again
+    if data.args().map(|args| !args.command().is_empty()).unwrap_or(false) {
+        return Ok(());
+    }
+
+    let user_id = message.sender.as_ref().map(api::Chat::id).unwrap_or_default();
+    let (locale, key) = match conversation::get(user_id, CONVERSATION).as_deref().and_then(|state| state.split_once(' ')) {
+        Some((locale, key)) => (locale.to_string(), key.to_string()),
+        None => return Ok(()),
+    };
+    let source = language::source().get(&key).cloned().unwrap_or_default();
+
+    let expected = format::placeholders(&source);
+    if format::placeholders(&message.text) != expected {
+        let placeholders = if expected.is_empty() {
+            "—".to_string()
+        } else {
+            expected.iter()
+                .map(|placeholder| format!("<code>{{{}}}</code>", utils::html::escape(placeholder)))
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+
+        data.reply(OutgoingMessage::html(lang.get_text("errors.translation_placeholders", vec![("placeholders", placeholders.as_str())]))).await?;
+        return Ok(());
+    }
+
+    // A text Telegram does not accept would break every message using it
+    let markup = if language::is_plain(&key) {
+        if utils::text::has_markup(&message.text) {
+            Some(lang.get_text("errors.translation_plain", vec![]))
+        } else {
+            None
+        }
+    } else {
+        utils::text::validate(&message.text)
+            .err()
+            .map(|near| lang.get_text("errors.translation_html", vec![("near", near.as_str())]))
+    };
+    if let Some(text) = markup {
+        data.reply(OutgoingMessage::html(text)).await?;
+        return Ok(());
+    }
+
+    tables::Translation::submit(&locale, &key, &message.text, user_id)?;
+    data.reply(OutgoingMessage::html(lang.get_text("texts.translation_submitted", vec![("key", key.as_str())]))).await?;
+
+    data.client.send_message(message.chat.pack(), next_key_message(lang, user_id, &locale, Some(&key))?).await?;
+
+    Ok(())
+}
+
+#[macro_rules_attribute(dyn_async!)]
+async fn translate_callback<'fut>(data: &'fut Data) -> Result<()> {
+    let client = data.client;
+    let callback = data.callback.unwrap();
+    let lang = data.language;
+
+    let user_id = callback.sender.id();
+    let (locale, key) = match conversation::get(user_id, CONVERSATION).as_deref().and_then(|state| state.split_once(' ')) {
+        Some((locale, key)) => (locale.to_string(), key.to_string()),
+        None => return data.answer_alert(lang.get_text("errors.translate_expired", vec![]).trim()).await,
+    };
+
+    if data.request.ends_with("cancel") {
+        conversation::finish(user_id);
+        client.edit_callback_message(callback, OutgoingMessage::html(lang.get_text("texts.translate_cancelled", vec![]))).await?;
+    } else {
+        client.edit_callback_message(callback, next_key_message(lang, user_id, &locale, Some(&key))?).await?;
+    }
+
+    Ok(())
+}
+
+// Ask a translator for the next key without a translation, after `after`, or tell them there is none left
+fn next_key_message(lang: I18n, user_id: i64, locale: &str, after: Option<&str>) -> Result<OutgoingMessage> {
+    // The keys already submitted wait for an owner
+    let pending = tables::Translation::pending()?;
+    let keys = language::untranslated(locale)
+        .into_iter()
+        .filter(|key| !pending.iter().any(|translation| translation.locale == locale && translation.key == *key))
+        .collect::<Vec<&str>>();
+
+    let key = match after {
+        Some(after) => keys.iter()
+            .find(|key| **key > after)
+            .or_else(|| keys.iter().find(|key| **key != after))
+            .copied(),
+        None => keys.first().copied(),
+    };
+    let key = match key {
+        Some(key) => key,
+        None => {
+            conversation::finish(user_id);
+            return Ok(OutgoingMessage::html(lang.get_text("texts.translate_done", vec![("locale", locale)])));
+        }
+    };
+
+    conversation::start(user_id, CONVERSATION, &format!("{} {}", locale, key));
+
+    let source = language::source().get(key).cloned().unwrap_or_default();
+    let remaining = keys.len().to_string();
+
+    Ok(OutgoingMessage::html(lang.get_text("texts.translate_key", vec![("locale", locale), ("key", key), ("source", source.trim_end()), ("remaining", remaining.as_str())]))
+        .reply_markup(&Keyboard::builder()
+            .button(Button::callback(lang.get_text("buttons.skip", vec![]), "translate skip"))
+            .row()
+            .button(Button::callback(lang.get_text("buttons.cancel", vec![]), "translate cancel"))
+            .build()?))
+}
+
+#[macro_rules_attribute(dyn_async!)]
+async fn translations_message<'fut>(data: &'fut Data) -> Result<()> {
+    let message = data.message.unwrap();
+    let lang = data.language;
+
+    if !handler::is_owner(message) {
+        return Ok(());
+    }
+
+    data.reply(pending_message(lang)?).await?;
+
+    Ok(())
+}
+
+#[macro_rules_attribute(dyn_async!)]
+async fn translation_callback<'fut>(data: &'fut Data) -> Result<()> {
+    let client = data.client;
+    let callback = data.callback.unwrap();
+    let lang = data.language;
+
+    if !config::is_owner(callback.sender.id()) {
+        return Ok(());
+    }
+
+    let review = match Review::decode(data.request) {
+        Some(review) => review,
+        None => return Ok(()),
+    };
+
+    // Someone else may have reviewed it already
+    match review {
+        Review::Approve(id) if tables::Translation::get(id).is_ok() => {
+            overrides::approve(id)?;
+            data.answer_toast(lang.get_text("texts.translation_approved", vec![]).trim()).await?;
+        }
+        Review::Reject(id) if tables::Translation::get(id).is_ok() => {
+            tables::Translation::delete(id)?;
+            data.answer_toast(lang.get_text("texts.translation_rejected", vec![]).trim()).await?;
+        }
+        _ => {}
+    }
+
+    client.edit_callback_message(callback, pending_message(lang)?).await?;
+
+    Ok(())
+}
+
+// Show the oldest translation waiting for an owner
+fn pending_message(lang: I18n) -> Result<OutgoingMessage> {
+    let pending = tables::Translation::pending()?;
+    let translation = match pending.first() {
+        Some(translation) => translation,
+        None => return Ok(OutgoingMessage::html(lang.get_text("texts.translations_empty", vec![]))),
+    };
+
+    let id = translation.id.to_string();
+    let user_id = translation.user_id.to_string();
+    let user = tables::User::get(translation.user_id).map(|user| user.name).unwrap_or_default();
+    let source = language::source().get(&translation.key).cloned().unwrap_or_default();
+    let remaining = pending.len().to_string();
+
+    let text = lang.get_text("texts.translation_pending", vec![
+        ("id", id.as_str()),
+        ("locale", translation.locale.as_str()),
+        ("key", translation.key.as_str()),
+        ("user", user.as_str()),
+        ("user_id", user_id.as_str()),
+        ("source", source.trim_end()),
+        ("text", translation.text.trim_end()),
+        ("remaining", remaining.as_str()),
+    ]);
+
+    // The submitted text may be as long as a message already
+    Ok(OutgoingMessage::html(utils::text::truncate(&text, utils::text::MESSAGE_LIMIT))
+        .reply_markup(&Keyboard::builder()
+            .button(Button::payload(lang.get_text("buttons.approve", vec![]), &Review::Approve(translation.id)))
+            .button(Button::payload(lang.get_text("buttons.reject", vec![]), &Review::Reject(translation.id)))
+            .build()?))
+}
+
+#[macro_rules_attribute(dyn_async!)]
+async fn export_message<'fut>(data: &'fut Data) -> Result<()> {
+    let client = data.client;
+    let message = data.message.unwrap();
+    let lang = data.language;
+
+    if !handler::is_owner(message) {
+        return Ok(());
+    }
+
+    let locales = overrides::export()?;
+    if locales.is_empty() {
+        data.reply(OutgoingMessage::html(lang.get_text("texts.translations_empty", vec![]))).await?;
+        return Ok(());
+    }
+
+    let directory = config::get().storage.backups.clone();
+    fs::create_dir_all(&directory)?;
+
+    for (locale, texts) in locales.iter() {
+        let path = Path::new(&directory).join(format!("translations-{}-{}.yml", locale, database::timestamp()));
+        fs::write(&path, loader::to_yaml(locale, texts)?)?;
+
+        let text = OutgoingMessage::html(lang.get_text("texts.translations_exported", vec![("locale", locale.as_str())]))
+            .document(path)
+            .reply_to(Some(message.id));
+        client.send_message(message.chat.pack(), text).await?;
+    }
+
+    Ok(())
+}
+
+
+pub fn initialize<'a>() -> Register<'a> {
+    Register::new()
+        .set_name("translate")
+        .set_enabled(true)
+        .append("message", translate_message, "translate$", Some(HandlerOptions { is_command: true, description: Some("Translate the bot"), filters: &[Filter::Private], ..Default::default() }))
+        .append("message", translate_message, "translate .+$", Some(HandlerOptions { is_command: true, filters: &[Filter::Private], ..Default::default() }))
+        .append("message", answer_message, ".", Some(HandlerOptions { filters: &[Filter::Private, Filter::Conversation(CONVERSATION)], ..Default::default() }))
+        .append("message", translations_message, "translations$", Some(HandlerOptions { is_command: true, description: Some("Review the submitted translations"), ..Default::default() }))
+        .append("message", export_message, "export_translations$", Some(HandlerOptions { is_command: true, description: Some("Export the approved translations"), ..Default::default() }))
+        .append("callback", translate_callback, "^translate (skip|cancel)$", None)
+        .append("callback", translation_callback, r"^translation (approve|reject) \d+$", None)
+        .build()
+}
@@ -1,17 +1,42 @@
 // SPDX-License-Identifier: MIT
 // Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
 
-#![allow(unused_variables)]
+use std::sync::Mutex;
 
-use rust_i18n::t;
+use lazy_static::lazy_static;
 use regex::Regex;
+use rust_i18n::t;
 
 use crate::database::tables;
 
+pub mod check;
+pub mod format;
+pub mod loader;
+pub mod overrides;
+pub mod preference;
+pub mod runtime;
+
+
+// Locales bundled from `./locales`, the first one is the default
+pub const LOCALES: &[&str] = &["en-GB", "pt-rBR"];
+// Texts sent as plain text, in alerts and toasts, besides the `buttons` ones
+const PLAIN_TEXTS: &[&str] = &["errors.translate_expired", "errors.book_not_found", "texts.translation_approved", "texts.translation_rejected"];
+
+lazy_static! {
+    // Texts of the default locale, the ones every other locale translates
+    static ref SOURCE: loader::Texts = loader::parse(include_str!("../locales/en-GB.yml"))
+        .ok()
+        .and_then(|mut locales| locales.remove(LOCALES[0]))
+        .unwrap_or_default();
+    // Codes of the locales that are not bundled, kept for as long as the bot runs
+    static ref CODES: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+    static ref CODE_REGEX: Regex = Regex::new(r"^[A-Za-z]{2,3}(?:[-_][A-Za-z0-9]{2,8})*$").unwrap();
+}
 
 #[derive(Copy, Clone)]
 pub struct I18n<'a> {
     default_language: &'a str,
+    // Whether the values given to the texts are HTML escaped, true unless the text is sent as plain text
     escape_html: bool,
     language: &'a str,
 }
@@ -19,30 +44,74 @@ pub struct I18n<'a> {
 impl<'a> Default for I18n<'a> {
     fn default() -> Self {
         Self {
-            default_language: "en-GB",
+            default_language: LOCALES[0],
             escape_html: true,
-            language: "en-GB",
+            language: LOCALES[0],
         }
     }
 }
 
 impl<'a> I18n<'a> {
+    // Get a text, trying the language, then its base language and then the default one
     pub fn get_text(&self, key: &str, items: Vec<(&str, &str)>) -> String {
-        let mut text = t!(&key, locale=self.get_language_code());
+        match self.lookup(key) {
+            Some(template) => format::named(self, &template, &items),
+            None => key.to_string(),
+        }
+    }
+
+    // Get the form of a text for `count`, like `texts.books.one` or `texts.books.other`,
+    // `count` is available to the text as `{count}`
+    pub fn get_plural(&self, key: &str, count: i64, items: Vec<(&str, &str)>) -> String {
+        let category = format::plural(self.language, count);
+        let count = count.to_string();
 
-        let iter = items.clone().into_iter();
-        for item in iter {
-            text = text.replace(&["{", item.0, "}"].join(""), item.1);
+        let mut items = items;
+        items.push(("count", count.as_str()));
+
+        let plural_key = format!("{}.{}", key, category.as_str());
+        if self.lookup(&plural_key).is_some() {
+            self.get_text(&plural_key, items)
+        } else {
+            self.get_text(&format!("{}.other", key), items)
         }
+    }
+
+    // Write a number the way the language does, like `1,234.5` or `1.234,5`
+    pub fn format_number(&self, value: f64) -> String {
+        let decimal_separator = self.lookup("formats.decimal_separator").unwrap_or_else(|| ".".to_string());
+        let thousands_separator = self.lookup("formats.thousands_separator").unwrap_or_else(|| ",".to_string());
+
+        format::number(value, &decimal_separator, &thousands_separator)
+    }
 
-        let re = Regex::new(format!(r#"((\w+)-?(\w+)?).{}"#, key).as_str()).unwrap();
-        if re.is_match(text.as_str()) {
-            self.set_language(self.default_language);
-            text = self.get_text(key, items);
-            self.set_language(self.language);
+    // Write the date of a UNIX timestamp the way the language does, like `19 October 2022`
+    pub fn format_date(&self, timestamp: i64) -> String {
+        let (year, month, day) = format::civil_date(timestamp);
+        let (year, month_number, day) = (year.to_string(), month.to_string(), day.to_string());
+        let month = self.get_text(&format::month_key(month), vec![]);
+
+        match self.lookup("formats.date") {
+            Some(template) => format::named(self, &template, &[("day", day.as_str()), ("month", month.trim()), ("month_number", month_number.as_str()), ("year", year.as_str())]),
+            None => format!("{}-{:0>2}-{:0>2}", year, month_number, day),
         }
+    }
 
-        text
+    // Get the locales a text is looked for in, in order
+    pub fn chain(&self) -> Vec<&'a str> {
+        let mut chain: Vec<&'a str> = candidates(self.language);
+
+        if !chain.contains(&self.default_language) {
+            chain.push(self.default_language);
+        }
+
+        chain
+    }
+
+    fn lookup(&self, key: &str) -> Option<String> {
+        self.chain()
+            .into_iter()
+            .find_map(|locale| translate(locale, key))
     }
 
     pub fn get_language(self, language_code: &'a str) -> Self {
@@ -54,13 +123,134 @@ impl<'a> I18n<'a> {
 
     }
 
-    pub fn get_language_code(&self) -> &str {
+    pub fn get_language_code(&self) -> &'a str {
         self.language
     }
 
-    pub fn set_language(mut self, language_code: &'a str) {
+    pub fn set_language(&mut self, language_code: &'a str) {
         self.language = language_code;
     }
+
+    pub fn is_escaping_html(&self) -> bool {
+        self.escape_html
+    }
+
+    pub fn set_escape_html(&mut self, escape_html: bool) {
+        self.escape_html = escape_html;
+    }
+}
+
+// Get a text of a locale, `None` if the locale does not have it; the approved translations come first,
+// then the locales loaded while running and then the bundled ones
+fn translate(locale: &str, key: &str) -> Option<String> {
+    if let Some(text) = overrides::get(locale, key).or_else(|| runtime::get(locale, key)) {
+        return Some(text);
+    }
+
+    let text = t!(key, locale=locale);
+
+    // Missing keys are given back as `locale.key`
+    if text == format!("{}.{}", locale, key) {
+        None
+    } else {
+        Some(text)
+    }
+}
+
+// Get the bundled locales, the ones loaded while running and the ones with approved translations
+pub fn locales() -> Vec<&'static str> {
+    let mut locales = LOCALES.to_vec();
+    let others = runtime::locales()
+        .into_iter()
+        .chain(overrides::locales().iter().map(|locale| intern(locale)));
+    for locale in others {
+        if !locales.contains(&locale) {
+            locales.push(locale);
+        }
+    }
+
+    locales
+}
+
+// Get a locale code that lives as long as the bot, as `I18n` keeps them by reference
+pub fn intern(code: &str) -> &'static str {
+    if let Some(locale) = LOCALES.iter().copied().find(|locale| *locale == code) {
+        return locale;
+    }
+
+    let mut codes = CODES.lock().unwrap();
+    if let Some(interned) = codes.iter().copied().find(|interned| *interned == code) {
+        return interned;
+    }
+
+    let interned: &'static str = Box::leak(code.to_string().into_boxed_str());
+    codes.push(interned);
+
+    interned
+}
+
+// Get the texts of the default locale by key, the ones every other locale translates
+pub fn source() -> &'static loader::Texts {
+    &SOURCE
+}
+
+// Get the keys a locale has no text for, its base language is not looked at
+pub fn untranslated(locale: &str) -> Vec<&'static str> {
+    SOURCE.keys()
+        .map(String::as_str)
+        .filter(|key| translate(locale, key).is_none())
+        .collect()
+}
+
+// Get the known locale written as `language_code`, like `pt-rBR` for `pt-br`, ignoring the other regions
+pub fn find(language_code: &str) -> Option<&'static str> {
+    let code = normalize(language_code);
+
+    locales()
+        .into_iter()
+        .find(|locale| normalize(locale) == code)
+}
+
+// Whether a text is sent as plain text, where HTML shows up as it is
+pub fn is_plain(key: &str) -> bool {
+    key.starts_with("buttons.") || PLAIN_TEXTS.contains(&key)
+}
+
+// Whether a text looks like a language code, like `pt`, `pt-BR` or `pt-rBR`
+pub fn is_code(code: &str) -> bool {
+    CODE_REGEX.is_match(code)
+}
+
+// Write a language code the same way whatever its case or separators, `pt-rBR` and `pt_BR` give `pt-br`
+fn normalize(code: &str) -> String {
+    code.to_lowercase().replace('_', "-").replace("-r", "-")
+}
+
+// Get the locales for a language code, the one that matches it first and then the ones
+// with the same base language, like `pt-rBR` for `pt-br`, `pt-BR` or `pt`
+fn candidates(language_code: &str) -> Vec<&'static str> {
+    let code = normalize(language_code);
+
+    let locales = locales();
+    let mut candidates: Vec<&'static str> = locales.iter()
+        .copied()
+        .filter(|locale| normalize(locale) == code)
+        .collect();
+    for locale in locales.iter().copied() {
+        if format::base(locale).eq_ignore_ascii_case(format::base(&code)) && !candidates.contains(&locale) {
+            candidates.push(locale);
+        }
+    }
+
+    candidates
+}
+
+// Get the locale closest to a language code, and the default locale if there is none
+pub fn resolve(language_code: &str) -> &'static str {
+    candidates(language_code)
+        .first()
+        .copied()
+        .unwrap_or(LOCALES[0])
 }
 
 pub fn new() -> I18n<'static> {
@@ -70,19 +260,40 @@ pub fn new() -> I18n<'static> {
 }
 
 pub fn from_user(id: i64) -> I18n<'static> {
-    let user = tables::User::get(id).unwrap();
+    // The default language if the user could not be written
+    let user = match tables::User::get(id) {
+        Ok(user) => user,
+        Err(_) => return new(),
+    };
 
-    let lang = new();
-    lang.set_language(user.language.as_str());
+    let mut lang = new();
+    lang.set_language(resolve(&user.language));
 
     lang
 }
 
 pub fn from_group(id: i64) -> I18n<'static> {
-    let group = tables::Group::get(id).unwrap();
+    // The default language if the group could not be written
+    let group = match tables::Group::get(id) {
+        Ok(group) => group,
+        Err(_) => return new(),
+    };
+
+    let mut lang = new();
+    lang.set_language(resolve(&group.language));
+
+    lang
+}
+
+pub fn from_channel(id: i64) -> I18n<'static> {
+    // The default language if the channel could not be written
+    let channel = match tables::Channel::get(id) {
+        Ok(channel) => channel,
+        Err(_) => return new(),
+    };
 
-    let lang = new();
-    lang.set_language(group.language.as_str());
+    let mut lang = new();
+    lang.set_language(resolve(&channel.language));
 
     lang
 }
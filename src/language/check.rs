@@ -0,0 +1,77 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::language::format;
+use crate::language::loader::{self, Result, Texts};
+
+
+// Differences between a locale and the default one
+#[derive(Debug, Default)]
+pub struct Report {
+    pub locale: String,
+    // Keys of the default locale the locale does not have
+    pub missing: Vec<String>,
+    // Keys the locale has but the default locale does not
+    pub extra: Vec<String>,
+    // Texts whose placeholders differ from the default locale
+    pub placeholders: Vec<Mismatch>,
+}
+
+impl Report {
+    pub fn has_errors(&self) -> bool {
+        !self.missing.is_empty() || !self.extra.is_empty() || !self.placeholders.is_empty()
+    }
+
+    // How many problems were found
+    pub fn errors(&self) -> usize {
+        self.missing.len() + self.extra.len() + self.placeholders.len()
+    }
+}
+
+// Placeholders of a text that differ from the default locale, like a `{bot_name}` that was translated
+#[derive(Debug, Default)]
+pub struct Mismatch {
+    pub key: String,
+    // Placeholders of the default locale the text does not have
+    pub missing: Vec<String>,
+    // Placeholders the text has but the default locale does not
+    pub extra: Vec<String>,
+}
+
+// Compare every locale of a directory against `default_locale`
+pub fn check<P: AsRef<Path>>(directory: P, default_locale: &str) -> Result<Vec<Report>> {
+    let locales = loader::load_dir(directory)?;
+    let default_texts = locales.get(default_locale)
+        .ok_or_else(|| format!("The default locale `{}` was not found", default_locale))?;
+
+    Ok(compare(default_texts, &locales, default_locale))
+}
+
+fn compare(default_texts: &Texts, locales: &BTreeMap<String, Texts>, default_locale: &str) -> Vec<Report> {
+    locales.iter()
+        .filter(|(locale, _)| locale.as_str() != default_locale)
+        .map(|(locale, texts)| Report {
+            locale: locale.clone(),
+            missing: default_texts.keys()
+                .filter(|key| !texts.contains_key(*key))
+                .cloned()
+                .collect(),
+            extra: texts.keys()
+                .filter(|key| !default_texts.contains_key(*key))
+                .cloned()
+                .collect(),
+            placeholders: texts.iter()
+                .filter_map(|(key, text)| Some((key, format::placeholders(default_texts.get(key)?), format::placeholders(text))))
+                .filter(|(_, expected, found)| expected != found)
+                .map(|(key, expected, found)| Mismatch {
+                    key: key.clone(),
+                    missing: expected.difference(&found).cloned().collect(),
+                    extra: found.difference(&expected).cloned().collect(),
+                })
+                .collect(),
+        })
+        .collect()
+}
@@ -0,0 +1,186 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use std::collections::BTreeSet;
+
+use crate::language::I18n;
+use crate::utils::html;
+
+
+// Names of the months, used to find them in the locales, like `formats.months.january`
+const MONTHS: [&str; 12] = [
+    "january", "february", "march", "april", "may", "june",
+    "july", "august", "september", "october", "november", "december",
+];
+
+// The plural forms a text can have, named after the CLDR categories
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Plural {
+    One,
+    Other,
+}
+
+impl Plural {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::One => "one",
+            Self::Other => "other",
+        }
+    }
+}
+
+// Get which plural form a language uses for `count`
+pub fn plural(language: &str, count: i64) -> Plural {
+    match base(language) {
+        // Portuguese uses the singular for zero too, `0 livro`
+        "pt" | "fr" => if count == 0 || count == 1 { Plural::One } else { Plural::Other },
+        _ => if count == 1 { Plural::One } else { Plural::Other },
+    }
+}
+
+// Get the language of a locale without its region, `pt-rBR` gives `pt`
+pub fn base(locale: &str) -> &str {
+    locale.split(|c| c == '-' || c == '_').next().unwrap_or(locale)
+}
+
+// Replace `{name}` by the value of `name`, `{name:number}` and `{name:date}` format it for the locale first,
+// `{{` and `}}` are written as `{` and `}`; values are HTML escaped when the language escapes HTML, unless
+// written as `{name:raw}`, for values that are HTML already
+pub fn named(lang: &I18n, template: &str, items: &[(&str, &str)]) -> String {
+    let mut text = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                text.push('}');
+            }
+            '{' => {
+                let mut placeholder = String::new();
+                let mut closed = false;
+                for c in chars.by_ref() {
+                    if c == '}' {
+                        closed = true;
+                        break;
+                    }
+                    placeholder.push(c);
+                }
+
+                let (name, kind) = match placeholder.split_once(':') {
+                    Some((name, kind)) => (name.trim(), Some(kind.trim())),
+                    None => (placeholder.trim(), None),
+                };
+                let value = items.iter().find(|item| item.0 == name).map(|item| item.1);
+                // Every value but the raw ones, the numbers and dates that do not parse included
+                let escape = |value: &str| if lang.is_escaping_html() {
+                    html::escape(value)
+                } else {
+                    value.to_string()
+                };
+
+                match (value, kind, closed) {
+                    (Some(value), Some("number"), true) => text.push_str(&value.parse().map(|number| lang.format_number(number)).unwrap_or_else(|_| escape(value))),
+                    (Some(value), Some("date"), true) => text.push_str(&value.parse().map(|timestamp| lang.format_date(timestamp)).unwrap_or_else(|_| escape(value))),
+                    (Some(value), Some("raw"), true) => text.push_str(value),
+                    (Some(value), _, true) => text.push_str(&escape(value)),
+                    // Keep what can not be replaced, so the mistake shows up
+                    (_, _, closed) => {
+                        text.push('{');
+                        text.push_str(&placeholder);
+                        if closed {
+                            text.push('}');
+                        }
+                    }
+                }
+            }
+            c => text.push(c),
+        }
+    }
+
+    text
+}
+
+// Get the placeholders of a template with their kind, like `name` and `count:number`
+pub fn placeholders(template: &str) -> BTreeSet<String> {
+    let mut placeholders = BTreeSet::new();
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+            }
+            '{' => {
+                let mut placeholder = String::new();
+                for c in chars.by_ref() {
+                    if c == '}' {
+                        break;
+                    }
+                    placeholder.push(c);
+                }
+
+                let placeholder = match placeholder.split_once(':') {
+                    Some((name, kind)) => format!("{}:{}", name.trim(), kind.trim()),
+                    None => placeholder.trim().to_string(),
+                };
+                placeholders.insert(placeholder);
+            }
+            _ => {}
+        }
+    }
+
+    placeholders
+}
+
+// Write a number with the separators of the locale, like `1,234.5` or `1.234,5`
+pub fn number(value: f64, decimal_separator: &str, thousands_separator: &str) -> String {
+    let formatted = format!("{:.2}", value.abs());
+    let (integer, fraction) = formatted.split_once('.').unwrap_or((&formatted, ""));
+    let fraction = fraction.trim_end_matches('0');
+
+    let mut text = String::new();
+    if value < 0.0 && formatted.chars().any(|c| c.is_ascii_digit() && c != '0') {
+        text.push('-');
+    }
+
+    for (index, digit) in integer.chars().enumerate() {
+        if index > 0 && (integer.len() - index) % 3 == 0 {
+            text.push_str(thousands_separator);
+        }
+        text.push(digit);
+    }
+
+    if !fraction.is_empty() {
+        text.push_str(decimal_separator);
+        text.push_str(fraction);
+    }
+
+    text
+}
+
+// Get the year, month and day of a UNIX timestamp, in UTC
+pub fn civil_date(timestamp: i64) -> (i64, u32, u32) {
+    // Days since 0000-03-01, so leap days are at the end of the year
+    let days = timestamp.div_euclid(86400) + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = (if month_index < 10 { month_index + 3 } else { month_index - 9 }) as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+// Get the key of the name of a month, from 1 to 12
+pub fn month_key(month: u32) -> String {
+    format!("formats.months.{}", MONTHS[(month.clamp(1, 12) - 1) as usize])
+}
@@ -0,0 +1,121 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use serde_yaml::{Mapping, Value};
+
+
+pub type Result<T> = std::result::Result<T, Box<dyn Error + Send + Sync>>;
+
+// Texts of a locale, keyed by their dotted path like `texts.start`
+pub type Texts = BTreeMap<String, String>;
+
+// Load every `.yml` file of a directory, keyed by locale code
+pub fn load_dir<P: AsRef<Path>>(directory: P) -> Result<BTreeMap<String, Texts>> {
+    let mut locales = BTreeMap::new();
+
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+        let is_yaml = path.extension()
+            .map(|extension| extension == "yml" || extension == "yaml")
+            .unwrap_or(false);
+
+        if is_yaml {
+            for (locale, texts) in load_file(&path)? {
+                locales.entry(locale)
+                    .or_insert_with(Texts::new)
+                    .extend(texts);
+            }
+        }
+    }
+
+    Ok(locales)
+}
+
+// Load a locale file, whose top level keys are the locale codes
+pub fn load_file<P: AsRef<Path>>(path: P) -> Result<BTreeMap<String, Texts>> {
+    let path = path.as_ref();
+    let content = fs::read_to_string(path)?;
+
+    parse(&content).map_err(|e| format!("{}: {}", path.display(), e).into())
+}
+
+// Read the content of a locale file
+pub fn parse(content: &str) -> Result<BTreeMap<String, Texts>> {
+    let value: Value = serde_yaml::from_str(content)?;
+
+    let mut locales = BTreeMap::new();
+    if let Value::Mapping(mapping) = value {
+        for (locale, value) in mapping {
+            if let Some(locale) = locale.as_str() {
+                let mut texts = Texts::new();
+                flatten(&value, String::new(), &mut texts);
+
+                locales.insert(locale.to_string(), texts);
+            }
+        }
+    }
+
+    Ok(locales)
+}
+
+// Write the texts of a locale in the format of the locale files, nested by the parts of the keys
+pub fn to_yaml(locale: &str, texts: &Texts) -> Result<String> {
+    let mut root = Mapping::new();
+    for (key, text) in texts {
+        insert(&mut root, &key.split('.').collect::<Vec<&str>>(), text);
+    }
+
+    let mut document = Mapping::new();
+    document.insert(Value::String(locale.to_string()), Value::Mapping(root));
+
+    Ok(serde_yaml::to_string(&document)?)
+}
+
+fn insert(mapping: &mut Mapping, path: &[&str], text: &str) {
+    let key = Value::String(path[0].to_string());
+
+    if path.len() == 1 {
+        mapping.insert(key, Value::String(text.to_string()));
+        return;
+    }
+
+    if !matches!(mapping.get(&key), Some(Value::Mapping(_))) {
+        mapping.insert(key.clone(), Value::Mapping(Mapping::new()));
+    }
+    if let Some(Value::Mapping(child)) = mapping.get_mut(&key) {
+        insert(child, &path[1..], text);
+    }
+}
+
+fn flatten(value: &Value, prefix: String, texts: &mut Texts) {
+    match value {
+        Value::Mapping(mapping) => {
+            for (key, value) in mapping {
+                let key = match key {
+                    Value::String(key) => key.clone(),
+                    Value::Number(key) => key.to_string(),
+                    Value::Bool(key) => key.to_string(),
+                    _ => continue,
+                };
+                let path = if prefix.is_empty() { key } else { format!("{}.{}", prefix, key) };
+
+                flatten(value, path, texts);
+            }
+        }
+        Value::String(text) => {
+            texts.insert(prefix, text.clone());
+        }
+        Value::Number(number) => {
+            texts.insert(prefix, number.to_string());
+        }
+        Value::Bool(boolean) => {
+            texts.insert(prefix, boolean.to_string());
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,75 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::RwLock;
+
+use lazy_static::lazy_static;
+use rusqlite::Error;
+
+use crate::database::tables;
+use crate::language::{self, loader::Texts};
+
+
+lazy_static! {
+    // Approved translations by locale and key, kept in memory as they are looked at for every text
+    static ref TEXTS: RwLock<HashMap<(String, String), String>> = RwLock::new(HashMap::new());
+}
+
+// Read the approved translations from the database
+pub fn load() -> Result<(), Error> {
+    let texts = tables::Translation::approved()?
+        .into_iter()
+        .map(|translation| {
+            language::intern(&translation.locale);
+            ((translation.locale, translation.key), translation.text)
+        })
+        .collect();
+
+    *TEXTS.write().unwrap() = texts;
+
+    Ok(())
+}
+
+// Approve a submitted translation, it is used right away
+pub fn approve(id: i64) -> Result<tables::Translation, Error> {
+    let translation = tables::Translation::get(id)?;
+    tables::Translation::approve(id)?;
+
+    language::intern(&translation.locale);
+    TEXTS.write().unwrap().insert((translation.locale.clone(), translation.key.clone()), translation.text.clone());
+
+    Ok(translation)
+}
+
+// Get the approved translation of a text
+pub fn get(locale: &str, key: &str) -> Option<String> {
+    TEXTS.read().unwrap()
+        .get(&(locale.to_string(), key.to_string()))
+        .cloned()
+}
+
+// Get the codes of the locales with approved translations
+pub fn locales() -> Vec<String> {
+    let mut locales = TEXTS.read().unwrap()
+        .keys()
+        .map(|(locale, _)| locale.clone())
+        .collect::<Vec<String>>();
+    locales.sort();
+    locales.dedup();
+
+    locales
+}
+
+// Get the approved translations of every locale, to write them as locale files
+pub fn export() -> Result<BTreeMap<String, Texts>, Error> {
+    let mut locales = BTreeMap::new();
+
+    for translation in tables::Translation::approved()? {
+        locales.entry(translation.locale)
+            .or_insert_with(Texts::new)
+            .insert(translation.key, translation.text);
+    }
+
+    Ok(locales)
+}
@@ -0,0 +1,68 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use crate::database::tables;
+use crate::language::format;
+
+
+// The languages a user reads books in, used to order search results, recommendations and browse views
+#[derive(Clone, Debug, PartialEq)]
+pub struct Preference {
+    // Base languages, like `pt`, the one of the user first and then the secondary ones
+    languages: Vec<String>,
+    // Whether the books in other languages are shown, after the ones in these languages
+    show_all: bool,
+}
+
+impl Preference {
+    pub fn new(language: &str, secondary_languages: &[String], show_all: bool) -> Self {
+        let mut languages: Vec<String> = Vec::new();
+        for language in std::iter::once(language).chain(secondary_languages.iter().map(String::as_str)) {
+            let base = format::base(language).to_lowercase();
+            if !base.is_empty() && !languages.contains(&base) {
+                languages.push(base);
+            }
+        }
+
+        Self {
+            languages: languages,
+            show_all: show_all,
+        }
+    }
+
+    pub fn from_user(user: &tables::User) -> Self {
+        Self::new(&user.language, &user.secondary_languages, user.show_all_languages)
+    }
+
+    pub fn languages(&self) -> &[String] {
+        &self.languages
+    }
+
+    pub fn show_all(&self) -> bool {
+        self.show_all
+    }
+
+    // Get where a book in `language` goes, lower first, `None` if it is not shown;
+    // books without a language are shown after the preferred ones
+    pub fn rank(&self, language: &str) -> Option<usize> {
+        let base = format::base(language).to_lowercase();
+
+        match self.languages.iter().position(|preferred| *preferred == base) {
+            Some(position) => Some(position),
+            None if self.show_all || base.is_empty() => Some(self.languages.len()),
+            None => None,
+        }
+    }
+
+    // Order items by the language they are in, keeping their order otherwise, and hide the ones not shown
+    pub fn sort<T, F: Fn(&T) -> &str>(&self, items: Vec<T>, language: F) -> Vec<T> {
+        let mut ranked = items.into_iter()
+            .filter_map(|item| self.rank(language(&item)).map(|rank| (rank, item)))
+            .collect::<Vec<(usize, T)>>();
+        ranked.sort_by_key(|(rank, _)| *rank);
+
+        ranked.into_iter()
+            .map(|(_, item)| item)
+            .collect()
+    }
+}
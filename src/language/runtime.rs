@@ -0,0 +1,129 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::time::{Duration, SystemTime};
+
+use lazy_static::lazy_static;
+use log::{error, info, warn};
+
+use crate::language::{self, loader::{self, Result, Texts}};
+
+
+lazy_static! {
+    static ref STATE: RwLock<State> = RwLock::new(State::default());
+}
+
+// Locales loaded from a directory while running, looked at before the bundled ones
+#[derive(Default)]
+struct State {
+    directory: Option<PathBuf>,
+    locales: BTreeMap<String, Texts>,
+    // Files of the directory when it was loaded, to know if it changed
+    fingerprint: Vec<(PathBuf, Option<SystemTime>, u64)>,
+}
+
+// Load the locales of a directory, replacing the ones loaded before, returns their codes;
+// if a file is not valid the locales loaded before are kept, and the directory is still watched
+pub fn load<P: AsRef<Path>>(directory: P) -> Result<Vec<&'static str>> {
+    let directory = directory.as_ref().to_path_buf();
+    STATE.write().unwrap().directory = Some(directory.clone());
+
+    let fingerprint = fingerprint(&directory)?;
+    let locales = loader::load_dir(&directory);
+
+    let mut state = STATE.write().unwrap();
+    // So a broken file is reported once, not on every check
+    state.fingerprint = fingerprint;
+    let mut locales = locales?;
+
+    // The codes are written in messages and used to pick a language, anything else is not a locale
+    locales.retain(|locale, _| {
+        if !language::is_code(locale) {
+            warn!("Skipping the locale `{}` of {}, it is not a language code", locale, directory.display());
+        }
+
+        language::is_code(locale)
+    });
+
+    let codes = locales.keys()
+        .map(|locale| language::intern(locale))
+        .collect();
+
+    state.locales = locales;
+
+    Ok(codes)
+}
+
+// Load the directory again
+pub fn reload() -> Result<Vec<&'static str>> {
+    match directory() {
+        Some(directory) => load(directory),
+        None => Err("No locales directory is configured".into()),
+    }
+}
+
+// Load the directory again if a file was added, removed or changed since it was loaded
+pub fn reload_if_changed() -> Result<bool> {
+    let directory = match directory() {
+        Some(directory) => directory,
+        None => return Ok(false),
+    };
+
+    if fingerprint(&directory)? == STATE.read().unwrap().fingerprint {
+        return Ok(false);
+    }
+
+    load(directory).map(|_| true)
+}
+
+// Check the directory every `interval`, forever
+pub async fn watch(interval: Duration) {
+    loop {
+        tokio::time::sleep(interval).await;
+
+        match reload_if_changed() {
+            Ok(true) => info!("Locales reloaded from {}", directory().unwrap_or_default().display()),
+            Ok(false) => {}
+            Err(e) => error!("Failed to reload the locales: {}", e),
+        }
+    }
+}
+
+pub fn directory() -> Option<PathBuf> {
+    STATE.read().unwrap().directory.clone()
+}
+
+// Get a text of a locale loaded from the directory
+pub fn get(locale: &str, key: &str) -> Option<String> {
+    STATE.read().unwrap()
+        .locales
+        .get(locale)?
+        .get(key)
+        .cloned()
+}
+
+// Get the codes of the locales loaded from the directory
+pub fn locales() -> Vec<&'static str> {
+    STATE.read().unwrap()
+        .locales
+        .keys()
+        .map(|locale| language::intern(locale))
+        .collect()
+}
+
+fn fingerprint(directory: &Path) -> Result<Vec<(PathBuf, Option<SystemTime>, u64)>> {
+    let mut fingerprint = Vec::new();
+
+    for entry in fs::read_dir(directory)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        fingerprint.push((entry.path(), metadata.modified().ok(), metadata.len()));
+    }
+    fingerprint.sort();
+
+    Ok(fingerprint)
+}
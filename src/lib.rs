@@ -8,10 +8,17 @@ extern crate macro_rules_attribute;
 extern crate rust_i18n;
 i18n!("./locales");
 
+pub mod api;
+pub mod config;
 pub mod database;
+pub mod dispatcher;
+pub mod error;
 pub mod handler;
 pub mod utils;
 pub mod handlers;
 pub mod language;
+pub mod logging;
+pub mod plugins;
+pub mod reader;
 
 pub use handler::handle_update;
@@ -0,0 +1,188 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use std::cell::RefCell;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::future::Future;
+use std::panic;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use log::{LevelFilter, error, warn};
+use log4rs::Handle;
+use log4rs::append::console::ConsoleAppender;
+use log4rs::append::rolling_file::RollingFileAppender;
+use log4rs::append::rolling_file::policy::compound::CompoundPolicy;
+use log4rs::append::rolling_file::policy::compound::roll::fixed_window::FixedWindowRoller;
+use log4rs::append::rolling_file::policy::compound::trigger::size::SizeTrigger;
+use log4rs::config::{Appender, Config, Logger, Root};
+use log4rs::encode::Encode;
+use log4rs::encode::json::JsonEncoder;
+use log4rs::encode::pattern::PatternEncoder;
+
+use crate::config;
+
+
+const PATTERN: &str = "{d(%Y-%m-%d %H:%M:%S)} {h({l:<5})} {t} - {m}{n}";
+// How often the rolled logs are checked for the old ones
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+tokio::task_local! {
+    static CONTEXT: RefCell<Context>;
+}
+
+// What was being handled when something was logged
+#[derive(Clone, Debug, Default)]
+pub struct Context {
+    // Sequential number of the update since the bot started
+    pub update_id: u64,
+    pub chat_id: Option<i64>,
+    // Name of the handler being run, see `Handler::get_name`
+    pub handler: Option<String>,
+}
+
+impl Context {
+    pub fn new(update_id: u64, chat_id: Option<i64>) -> Self {
+        Self {
+            update_id: update_id,
+            chat_id: chat_id,
+            handler: None,
+        }
+    }
+}
+
+impl fmt::Display for Context {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "update={}", self.update_id)?;
+        if let Some(chat_id) = self.chat_id {
+            write!(f, " chat={}", chat_id)?;
+        }
+        if let Some(handler) = self.handler.as_ref() {
+            write!(f, " handler={}", handler)?;
+        }
+
+        Ok(())
+    }
+}
+
+// Run `future` with `context` available to `context()` and the panic hook
+pub async fn scope<F: Future>(context: Context, future: F) -> F::Output {
+    CONTEXT.scope(RefCell::new(context), future).await
+}
+
+// Set the handler being run in the current context
+pub fn set_handler(name: String) {
+    let _ = CONTEXT.try_with(|context| context.borrow_mut().handler = Some(name));
+}
+
+// Get the current context, if any
+pub fn context() -> Option<Context> {
+    CONTEXT.try_with(|context| context.borrow().clone()).ok()
+}
+
+// Initialize the logger from the configuration
+pub fn init(logging: &config::Logging) -> Result<Handle, Box<dyn Error>> {
+    let level = parse_level(&logging.level)?;
+
+    let console = ConsoleAppender::builder()
+        .encoder(encoder(logging.json))
+        .build();
+    let mut builder = Config::builder()
+        .appender(Appender::builder().build("stdout", Box::new(console)));
+    let mut root = Root::builder().appender("stdout");
+
+    if !logging.file.path.is_empty() {
+        let file = &logging.file;
+        let roller = FixedWindowRoller::builder()
+            .base(1)
+            .build(&format!("{}.{{}}", file.path), file.max_files)
+            .map_err(|e| e.to_string())?;
+        let policy = CompoundPolicy::new(Box::new(SizeTrigger::new(file.max_size)), Box::new(roller));
+        let appender = RollingFileAppender::builder()
+            .encoder(encoder(logging.json))
+            .build(&file.path, Box::new(policy))?;
+
+        builder = builder.appender(Appender::builder().build("file", Box::new(appender)));
+        root = root.appender("file");
+    }
+
+    for (module, module_level) in logging.modules.iter() {
+        builder = builder.logger(Logger::builder().build(module, parse_level(module_level)?));
+    }
+
+    let handle = log4rs::init_config(builder.build(root.build(level))?)?;
+    set_panic_hook();
+
+    Ok(handle)
+}
+
+// Remove the rolled logs older than `max_age` days, now and every hour as the file keeps rolling
+pub async fn prune(file: config::LogFile) {
+    if file.path.is_empty() || file.max_age == 0 {
+        return;
+    }
+
+    let max_age = Duration::from_secs(file.max_age * 24 * 60 * 60);
+    loop {
+        remove_old_logs(&file.path, max_age);
+        tokio::time::sleep(PRUNE_INTERVAL).await;
+    }
+}
+
+pub fn parse_level(level: &str) -> Result<LevelFilter, Box<dyn Error>> {
+    level.parse()
+        .map_err(|_| format!("Unknown log level `{}`", level).into())
+}
+
+fn encoder(json: bool) -> Box<dyn Encode> {
+    if json {
+        Box::new(JsonEncoder::new())
+    } else {
+        Box::new(PatternEncoder::new(PATTERN))
+    }
+}
+
+// Log the panics, with the context they happened in, instead of printing them to stderr
+fn set_panic_hook() {
+    panic::set_hook(Box::new(|info| {
+        match context() {
+            Some(context) => error!("Handler panicked ({}): {}", context, info),
+            None => error!("Panicked: {}", info),
+        }
+    }));
+}
+
+// Delete the rolled log files, like `myne_books.log.2`, older than `max_age`
+fn remove_old_logs(path: &str, max_age: Duration) {
+    let path = Path::new(path);
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let prefix = match path.file_name() {
+        Some(name) => format!("{}.", name.to_string_lossy()),
+        None => return,
+    };
+
+    let entries = match fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let is_rolled = entry.file_name().to_string_lossy().starts_with(&prefix);
+        let is_old = entry.metadata()
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .map(|age| age > max_age)
+            .unwrap_or(false);
+
+        if is_rolled && is_old {
+            if let Err(e) = fs::remove_file(entry.path()) {
+                warn!("Failed to remove the old log {}: {}", entry.path().display(), e);
+            }
+        }
+    }
+}
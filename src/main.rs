@@ -4,76 +4,125 @@
 #![allow(unused_must_use)]
 
 use std::error::Error;
-use std::fs::File;
-use std::io::Read;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
 
-use log::LevelFilter;
-use log4rs::append::console::ConsoleAppender;
-use log4rs::config::{Appender, Root};
-use log4rs::Config;
+use clap::{Parser, Subcommand};
+use log::{error, info, warn};
 use grammers_client::{Client, Config as GConfig, InitParams};
 use grammers_session::Session;
-use tokio::{runtime, task};
-use serde_derive::Deserialize;
+use tokio::{runtime, signal};
 
+use myne_books::config;
 use myne_books::handler;
 use myne_books::database;
-use myne_books::handle_update;
+use myne_books::database::{backup, presence};
+use myne_books::language;
+use myne_books::logging;
+use myne_books::dispatcher::Dispatcher;
 
 
-#[derive(Debug, Deserialize)]
-struct TConfig {
-    grammers: Grammers,
-    myne: Myne,
-}
+type Result<T> = std::result::Result<T, Box<dyn Error + Send + Sync>>;
 
-#[derive(Debug, Deserialize)]
-struct Grammers {
-    api_id: i32,
-    api_hash: String,
-    bot_token: String,
+/// A bot to read or download books without leaving Telegram
+#[derive(Parser)]
+#[clap(version, about)]
+struct Cli {
+    /// Path of the configuration file
+    #[clap(long, global = true, default_value = "config.toml")]
+    config: String,
+    /// Path of the SQLite database, overrides `database.path`
+    #[clap(long, global = true)]
+    db: Option<String>,
+    #[clap(subcommand)]
+    command: Option<Command>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
-struct Myne {
-    prefixes: Vec<String>,
+#[derive(Subcommand)]
+enum Command {
+    /// Connect to Telegram and handle the updates (default)
+    Run {
+        /// Path of the Telegram session file, overrides `storage.session`
+        #[clap(long)]
+        session: Option<String>,
+    },
+    /// Create or update the database tables
+    Migrate,
+    /// Import a JSON export, or every JSON export inside a directory
+    Import {
+        path: String,
+    },
+    /// Export every record as JSON
+    Export {
+        path: String,
+    },
+    /// Copy the database into the configured backups directory
+    Backup,
+    /// Replace the database with a backup
+    Restore {
+        path: String,
+    },
+    /// Check if the configuration file is valid
+    CheckConfig,
+    /// Manage the locale files
+    Locales {
+        #[clap(subcommand)]
+        command: LocalesCommand,
+    },
 }
 
-async fn async_main() -> Result<(), Box<dyn Error>> {
-    let stdout = ConsoleAppender::builder().build();
+#[derive(Subcommand)]
+enum LocalesCommand {
+    /// Compare every locale against the default one
+    Check {
+        /// Directory with the locale files
+        #[clap(default_value = "./locales")]
+        directory: String,
+    },
+}
 
-    let config = Config::builder()
-        .appender(Appender::builder()
-        .build("stdout", Box::new(stdout)))
-        .build(Root::builder().appender("stdout").build(LevelFilter::Info))
-        .unwrap();
+async fn async_main(config_path: String, db_path: Option<String>, session_path: Option<String>) -> std::result::Result<(), Box<dyn Error>> {
+    // Get the configuration
+    let decoded = config::load(&config_path)?;
+    config::set(decoded.clone());
 
-    let _handle = log4rs::init_config(config).unwrap();
+    let _handle = logging::init(&decoded.logging)?;
+    tokio::spawn(logging::prune(decoded.logging.file.clone()));
 
     // Connect the database
+    database::set_path(&db_path.unwrap_or(decoded.database.path));
     let dbc = database::connect().unwrap();
     dbc.initialize();
 
-    // Get the configuration
-    let mut toml_str = String::new();
-    File::open("config.toml")
-        .and_then(|mut f| f.read_to_string(&mut toml_str))
-        .unwrap();
-    let decoded: TConfig = toml::from_str(&toml_str)?;
     let api_id = decoded.grammers.api_id;
     let api_hash = decoded.grammers.api_hash;
     let bot_token = decoded.grammers.bot_token;
+    let session_path = session_path.unwrap_or(decoded.storage.session);
 
     let prefixes = decoded.myne.prefixes;
 
+    // Load the locales of the operator over the bundled ones
+    if !decoded.locales.directory.is_empty() {
+        match language::runtime::load(&decoded.locales.directory) {
+            Ok(locales) => info!("Loaded the locales {:?} from {}", locales, decoded.locales.directory),
+            Err(e) => error!("Failed to load the locales from {}, using the bundled ones: {}", decoded.locales.directory, e),
+        }
+
+        if decoded.locales.reload_interval > 0 {
+            tokio::spawn(language::runtime::watch(Duration::from_secs(decoded.locales.reload_interval)));
+        }
+    }
+
     // Starts the bot
     let mut client = Client::connect(GConfig {
-        session: Session::load_file_or_create("myne_books.session")?,
+        session: Session::load_file_or_create(&session_path)?,
         api_id: api_id,
         api_hash: api_hash.clone(),
 
         params: InitParams {
-            flood_sleep_threshold: Some(120),
+            flood_sleep_threshold: Some(decoded.limits.flood_sleep_threshold),
             ..Default::default()
         },
     })
@@ -83,38 +132,206 @@ async fn async_main() -> Result<(), Box<dyn Error>> {
     // Sign in if haven't already
     if !client.is_authorized().await? {
         client.bot_sign_in(&bot_token, api_id, &api_hash).await?;
-        client.session().save_to_file("myne_books.session")?;
+        client.session().save_to_file(&session_path)?;
     }
 
     // Initialize the modules
     let mut handler_list = Vec::new();
-    handler::initialize(&mut handler_list);
-
-    // Handle the updates
-    while let Some(update) = client.next_update().await? {
-        let handle = client.clone();
-        let handle_list = handler_list.clone();
-        let prefix = prefixes.clone();
-        let me = me_user.clone();
-
-        task::spawn(async move {
-            match handle_update(handle, update, handle_list, prefix, me).await {
-                Ok(_) => {}
-                Err(e) => eprintln!("Error handling updates!: {}", e)
+    if let Err(e) = handler::initialize(&mut handler_list) {
+        error!("Failed to initialize the handlers: {}", e);
+        return Err(e);
+    }
+
+    // Handle the updates until a shutdown signal arrives
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+
+    let mut dispatcher = Dispatcher::new(Arc::new(client.clone()), handler_list, prefixes, me_user.into(), &decoded.limits);
+    let mut result = Ok(());
+    loop {
+        let update = tokio::select! {
+            update = client.next_update() => update,
+            _ = &mut shutdown => {
+                info!("Shutdown signal received, stopping...");
+                break;
+            }
+        };
+
+        match update {
+            Ok(Some(update)) => dispatcher.dispatch(update.into()),
+            Ok(None) => break,
+            Err(e) => {
+                error!("Failed to get the next update: {}", e);
+                result = Err(e.into());
+                break;
             }
-        });
+        }
     }
 
+    // Wait for the updates being handled
+    let timeout = Duration::from_secs(decoded.limits.shutdown_timeout);
+    if !dispatcher.shutdown(timeout).await {
+        warn!("Some updates were still being handled after {} seconds, exiting anyway", timeout.as_secs());
+    }
+
+    // Write what was kept in memory
+    presence::flush();
+
     // Save the session and exit
-    client.session().save_to_file("myne_books.session")?;
+    client.session().save_to_file(&session_path)?;
+    info!("Session saved, bye!");
+
+    result
+}
+
+// Wait for Ctrl+C or, on Unix, SIGTERM
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        let mut terminate = signal::unix::signal(signal::unix::SignalKind::terminate())
+            .expect("Failed to listen for SIGTERM");
+
+        tokio::select! {
+            _ = signal::ctrl_c() => {}
+            _ = terminate.recv() => {}
+        }
+    }
+
+    #[cfg(not(unix))]
+    signal::ctrl_c().await;
+}
+
+// Run the commands that only need the database
+fn maintenance(config_path: &str, db_path: Option<String>, command: Command) -> Result<()> {
+    // The configuration is optional here, but must be valid if it exists
+    let config = if Path::new(config_path).exists() {
+        config::load(config_path)?
+    } else {
+        config::Config::default()
+    };
+    database::set_path(&db_path.unwrap_or(config.database.path));
+
+    match command {
+        Command::Migrate => migrate(),
+        Command::Import { path } => migrate().and_then(|_| import(&path)),
+        Command::Export { path } => {
+            let dump = backup::export(&path)?;
+            println!("Exported {} users, {} groups and {} channels to {}", dump.users.len(), dump.groups.len(), dump.channels.len(), path);
+
+            Ok(())
+        }
+        Command::Backup => {
+            let path = backup::backup(&config.storage.backups)?;
+            println!("Database backup written to {}", path.display());
+
+            Ok(())
+        }
+        Command::Restore { path } => {
+            backup::restore(&path)?;
+            println!("Database restored from {}", path);
+
+            Ok(())
+        }
+        _ => unreachable!(),
+    }
+}
+
+fn migrate() -> Result<()> {
+    let dbc = database::connect()?;
+    dbc.initialize()?;
+
+    println!("Database {} is up to date", database::get_path());
 
     Ok(())
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
-    runtime::Builder::new_current_thread()
+fn import(path: &str) -> Result<()> {
+    let mut files = Vec::new();
+
+    if Path::new(path).is_dir() {
+        for entry in fs::read_dir(path)? {
+            let file = entry?.path();
+            if file.extension().map(|extension| extension == "json").unwrap_or(false) {
+                files.push(file);
+            }
+        }
+        files.sort();
+    } else {
+        files.push(path.into());
+    }
+
+    for file in files {
+        let dump = backup::import(&file.to_string_lossy())?;
+        println!("Imported {} users, {} groups and {} channels from {}", dump.users.len(), dump.groups.len(), dump.channels.len(), file.display());
+    }
+
+    Ok(())
+}
+
+fn check_config(config_path: &str) -> Result<()> {
+    config::load(config_path)?;
+    println!("Configuration {} is valid", config_path);
+
+    Ok(())
+}
+
+fn check_locales(directory: &str) -> Result<()> {
+    let reports = language::check::check(directory, language::new().get_language_code())?;
+    let mut errors = 0;
+
+    for report in reports.iter() {
+        for key in report.missing.iter() {
+            println!("{}: missing key `{}`", report.locale, key);
+        }
+        for key in report.extra.iter() {
+            println!("{}: extra key `{}`", report.locale, key);
+        }
+        for mismatch in report.placeholders.iter() {
+            for placeholder in mismatch.missing.iter() {
+                println!("{}: `{}` is missing the `{{{}}}` placeholder", report.locale, mismatch.key, placeholder);
+            }
+            for placeholder in mismatch.extra.iter() {
+                println!("{}: `{}` has the unknown `{{{}}}` placeholder", report.locale, mismatch.key, placeholder);
+            }
+        }
+
+        errors += report.errors();
+    }
+
+    if errors > 0 {
+        return Err(format!("Found {} problems in the locales", errors).into());
+    }
+
+    println!("Checked {} locales, no problems found", reports.len() + 1);
+
+    Ok(())
+}
+
+fn main() -> std::result::Result<(), Box<dyn Error>> {
+    let cli = Cli::parse();
+
+    let result = match cli.command.unwrap_or(Command::Run { session: None }) {
+        Command::Run { session } => return run(cli.config, cli.db, session),
+        Command::CheckConfig => check_config(&cli.config),
+        Command::Locales { command: LocalesCommand::Check { directory } } => check_locales(&directory),
+        command => maintenance(&cli.config, cli.db, command),
+    };
+
+    result.map_err(|e| e as Box<dyn Error>)
+}
+
+fn run(config_path: String, db_path: Option<String>, session_path: Option<String>) -> std::result::Result<(), Box<dyn Error>> {
+    // The configuration is read again by `async_main`, but the runtime must be built first
+    let workers = config::load(&config_path)?.limits.workers;
+
+    let mut builder = runtime::Builder::new_multi_thread();
+    if workers > 0 {
+        builder.worker_threads(workers);
+    }
+
+    builder
         .enable_all()
         .build()
         .unwrap()
-        .block_on(async_main())
+        .block_on(async_main(config_path, db_path, session_path))
 }
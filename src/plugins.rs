@@ -0,0 +1,94 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::RwLock;
+
+use lazy_static::lazy_static;
+use rusqlite::Error;
+
+use crate::database::tables;
+
+
+// Plugins that can not be disabled, or no one could enable them again
+pub const PROTECTED: &[&str] = &["plugins"];
+
+lazy_static! {
+    static ref STATE: RwLock<State> = RwLock::new(State::default());
+}
+
+// Whether each plugin handles updates, kept in memory as it is checked on every update
+#[derive(Default)]
+struct State {
+    // Plugin states given by the plugins themselves and the configuration
+    defaults: BTreeMap<String, bool>,
+    // Plugin states set at runtime, by name and chat ID
+    overrides: HashMap<(String, i64), bool>,
+}
+
+// Make a plugin known, with the state used when no one changed it at runtime
+pub fn register(name: &str, enabled: bool) {
+    STATE.write().unwrap().defaults.insert(name.to_string(), enabled);
+}
+
+// Read the states set at runtime from the database
+pub fn load() -> Result<(), Error> {
+    let overrides = tables::Plugin::all()?
+        .into_iter()
+        .map(|plugin| ((plugin.name, plugin.chat_id), plugin.enabled))
+        .collect();
+
+    STATE.write().unwrap().overrides = overrides;
+
+    Ok(())
+}
+
+// Whether a plugin handles the updates of a chat, the state of the chat comes before the global one
+pub fn is_enabled(name: &str, chat_id: Option<i64>) -> bool {
+    let state = STATE.read().unwrap();
+
+    chat_id
+        .and_then(|chat_id| state.overrides.get(&(name.to_string(), chat_id)))
+        .or_else(|| state.overrides.get(&(name.to_string(), tables::GLOBAL)))
+        .or_else(|| state.defaults.get(name))
+        .copied()
+        .unwrap_or(false)
+}
+
+// Enable or disable a plugin in a chat, or in every chat if `chat_id` is `None`
+pub fn set(name: &str, chat_id: Option<i64>, enabled: bool) -> Result<(), Error> {
+    let chat_id = chat_id.unwrap_or(tables::GLOBAL);
+    tables::Plugin::set(name, chat_id, enabled)?;
+
+    STATE.write().unwrap().overrides.insert((name.to_string(), chat_id), enabled);
+
+    Ok(())
+}
+
+// Move the states set in a chat to another one, when a group becomes a supergroup
+pub fn move_chat(from: i64, to: i64) -> Result<(), Error> {
+    tables::Plugin::move_chat(from, to)?;
+
+    let mut state = STATE.write().unwrap();
+    let moved = state.overrides
+        .iter()
+        .filter(|((_, chat_id), _)| *chat_id == from)
+        .map(|((name, _), enabled)| (name.clone(), *enabled))
+        .collect::<Vec<(String, bool)>>();
+    for (name, enabled) in moved {
+        state.overrides.remove(&(name.clone(), from));
+        state.overrides.insert((name, to), enabled);
+    }
+
+    Ok(())
+}
+
+// Whether a plugin with this name was registered
+pub fn exists(name: &str) -> bool {
+    STATE.read().unwrap().defaults.contains_key(name)
+}
+
+// Get the name of every registered plugin, sorted
+pub fn names() -> Vec<String> {
+    STATE.read().unwrap().defaults.keys().cloned().collect()
+}
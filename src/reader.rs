@@ -0,0 +1,21 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use std::path::PathBuf;
+
+use crate::config;
+
+pub mod toc;
+
+
+// Formats of the books the reader opens
+pub const EXTENSIONS: &[&str] = &["epub", "fb2", "pdf"];
+
+// Get the file of a book, named by its ID in the books directory, like `12.epub`
+pub fn find(book: i64) -> Option<PathBuf> {
+    let directory = PathBuf::from(&config::get().storage.books);
+
+    EXTENSIONS.iter()
+        .map(|extension| directory.join(format!("{}.{}", book, extension)))
+        .find(|path| path.is_file())
+}
@@ -0,0 +1,266 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use std::fmt;
+use std::path::Path;
+
+use crate::error::{Error, Result};
+use crate::language::I18n;
+use crate::utils::{Button, Keyboard, Paginator, Payload};
+
+mod epub;
+mod fb2;
+mod pdf;
+
+pub use epub::from_epub;
+pub use fb2::from_fb2;
+pub use pdf::from_pdf;
+
+
+// Chapters shown in each page of the contents
+pub const CHAPTERS_PER_PAGE: usize = 8;
+// Characters of a chapter title shown in its button
+const TITLE_LIMIT: usize = 40;
+
+// A chapter of a book and the ones inside it, from the EPUB navigation, the FB2 sections or the PDF outline
+#[derive(Clone, Debug, PartialEq)]
+pub struct Chapter {
+    pub title: String,
+    // The first page of the chapter, from 1
+    pub page: usize,
+    pub children: Vec<Chapter>,
+}
+
+// The table of contents of a book
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Contents {
+    pub chapters: Vec<Chapter>,
+}
+
+// A chapter in the order it is read, with how deep it is nested
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry<'a> {
+    pub title: &'a str,
+    pub page: usize,
+    pub depth: usize,
+}
+
+// Where a button of the reader takes the user
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Navigation {
+    // A page of a book
+    Read {
+        book: i64,
+        page: usize,
+    },
+    // A page of the contents of a book, opened from the page being read
+    Contents {
+        book: i64,
+        reading: usize,
+        page: usize,
+    },
+}
+
+impl Payload for Navigation {
+    fn encode(&self) -> String {
+        match self {
+            Self::Read { book, page } => format!("read {} {}", book, page),
+            Self::Contents { book, reading, page } => format!("contents {} {} {}", book, reading, page),
+        }
+    }
+
+    fn decode(data: &str) -> Option<Self> {
+        let mut words = data.split(' ');
+
+        let navigation = match words.next()? {
+            "read" => Self::Read {
+                book: words.next()?.parse().ok()?,
+                page: words.next()?.parse().ok()?,
+            },
+            "contents" => Self::Contents {
+                book: words.next()?.parse().ok()?,
+                reading: words.next()?.parse().ok()?,
+                page: words.next()?.parse().ok()?,
+            },
+            _ => return None,
+        };
+
+        match words.next() {
+            Some(_) => None,
+            None => Some(navigation),
+        }
+    }
+}
+
+impl Chapter {
+    pub fn new<T: Into<String>>(title: T, page: usize) -> Self {
+        Self {
+            title: title.into(),
+            page: page,
+            children: Vec::new(),
+        }
+    }
+
+    pub fn child(mut self, chapter: Chapter) -> Self {
+        self.children.push(chapter);
+
+        self
+    }
+}
+
+impl Contents {
+    pub fn new(chapters: Vec<Chapter>) -> Self {
+        Self { chapters: chapters }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chapters.is_empty()
+    }
+
+    // Get every chapter in the order it is read, the parents before their children
+    pub fn entries(&self) -> Vec<Entry<'_>> {
+        fn walk<'a>(chapters: &'a [Chapter], depth: usize, entries: &mut Vec<Entry<'a>>) {
+            for chapter in chapters.iter() {
+                entries.push(Entry {
+                    title: chapter.title.as_str(),
+                    page: chapter.page,
+                    depth: depth,
+                });
+                walk(&chapter.children, depth + 1, entries);
+            }
+        }
+
+        let mut entries = Vec::new();
+        walk(&self.chapters, 0, &mut entries);
+
+        entries
+    }
+
+    // Get the index of the entry being read in `page`, the deepest of the last ones starting before it
+    pub fn current(&self, page: usize) -> Option<usize> {
+        self.entries()
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.page <= page)
+            .max_by_key(|(index, entry)| (entry.page, *index))
+            .map(|(index, _)| index)
+    }
+
+    // Get the last page a chapter starts at, the reader does not go past it
+    pub fn last_page(&self) -> usize {
+        self.entries()
+            .iter()
+            .map(|entry| entry.page)
+            .max()
+            .unwrap_or(1)
+    }
+
+    // Get the page of the contents with the chapter being read in `page`
+    pub fn page_of(&self, page: usize) -> usize {
+        self.current(page)
+            .map(|index| index / CHAPTERS_PER_PAGE + 1)
+            .unwrap_or(1)
+    }
+
+    // Build the button of the reader that opens the contents in the page with the chapter being read
+    pub fn button(&self, lang: I18n, book: i64, reading: usize) -> Button {
+        Button::payload(lang.get_text("buttons.contents", vec![]), &Navigation::Contents {
+            book: book,
+            reading: reading,
+            page: self.page_of(reading),
+        })
+    }
+
+    // Build the keyboard of a page of the contents, a chapter in each row indented by how deep it is,
+    // the one being read in `reading` marked, pressing one opens its first page; the last row goes back to `reading`
+    pub fn keyboard(&self, lang: I18n, book: i64, reading: usize, page: usize) -> Result<Keyboard> {
+        let entries = self.entries();
+        let current = self.current(reading);
+        let paginator = Paginator::for_items(format!("contents {} {}", book, reading), page, entries.len(), CHAPTERS_PER_PAGE);
+
+        let mut builder = Keyboard::builder().columns(1);
+        for index in paginator.range(entries.len(), CHAPTERS_PER_PAGE) {
+            let entry = &entries[index];
+            let marker = if Some(index) == current { "▶ " } else { "" };
+            let text = format!("{}{}{}", "· ".repeat(entry.depth), marker, shorten(entry.title));
+
+            builder = builder.button(Button::payload(text, &Navigation::Read { book: book, page: entry.page }));
+        }
+
+        builder.paginator(&paginator)
+            .button(Button::payload(lang.get_text("buttons.back", vec![]), &Navigation::Read { book: book, page: reading }))
+            .build()
+    }
+}
+
+// Read the table of contents of a book, by the extension of its file
+pub fn from_file(path: &Path) -> Result<Contents> {
+    let extension = path.extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+
+    match extension.as_str() {
+        "epub" => from_epub(path),
+        "fb2" => from_fb2(path),
+        "pdf" => from_pdf(path),
+        _ => Err(Error::other(format!("Can not read the contents of `{}`", path.display()))),
+    }
+}
+
+// Add a chapter read from a book; the ones without a title give their place to their children and the ones
+// without a page start where their first child does
+fn push(chapters: &mut Vec<Chapter>, title: String, page: Option<usize>, children: Vec<Chapter>) {
+    let page = page.or_else(|| children.first().map(|child| child.page));
+
+    match page {
+        Some(page) if !title.is_empty() => chapters.push(Chapter {
+            title: title,
+            page: page,
+            children: children,
+        }),
+        _ => chapters.extend(children),
+    }
+}
+
+fn parse_xml(text: &str) -> Result<roxmltree::Document<'_>> {
+    // The XHTML documents of EPUBs start with a doctype
+    let options = roxmltree::ParsingOptions {
+        allow_dtd: true,
+        ..roxmltree::ParsingOptions::default()
+    };
+
+    roxmltree::Document::parse_with_options(text, options).map_err(invalid)
+}
+
+// Get the text inside an element, its spaces and lines collapsed
+fn text_of(node: roxmltree::Node) -> String {
+    node.descendants()
+        .filter_map(|node| if node.is_text() { node.text() } else { None })
+        .collect::<Vec<_>>()
+        .join(" ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn invalid<E: fmt::Display>(e: E) -> Error {
+    Error::other(format!("The book could not be read: {}", e))
+}
+
+// Cut a title that does not fit in a button
+fn shorten(title: &str) -> String {
+    let title = title.trim();
+    if title.chars().count() <= TITLE_LIMIT {
+        return title.to_string();
+    }
+
+    let mut title = title.chars()
+        .take(TITLE_LIMIT - 1)
+        .collect::<String>()
+        .trim_end()
+        .to_string();
+    title.push('…');
+
+    title
+}
@@ -0,0 +1,193 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use roxmltree::Node;
+use zip::ZipArchive;
+
+use super::{invalid, parse_xml, push, text_of, Chapter, Contents};
+use crate::error::{Error, Result};
+
+
+// Namespace of the `epub:type` attribute
+const OPS_NAMESPACE: &str = "http://www.idpf.org/2007/ops";
+
+// A file listed in the manifest of the book
+struct Item<'a> {
+    // Where it is in the archive
+    path: String,
+    media_type: &'a str,
+    properties: &'a str,
+}
+
+// Read the table of contents of an EPUB, from its navigation document or, in EPUB 2 books, its NCX;
+// the page of a chapter is the position of its file in the reading order, from 1
+pub fn from_epub(path: &Path) -> Result<Contents> {
+    let mut archive = ZipArchive::new(File::open(path)?).map_err(invalid)?;
+
+    let container = read(&mut archive, "META-INF/container.xml")?;
+    let container = parse_xml(&container)?;
+    let package_path = container.descendants()
+        .find(|node| node.has_tag_name("rootfile"))
+        .and_then(|node| node.attribute("full-path"))
+        .ok_or_else(|| Error::other("The book has no package document"))?;
+
+    let package = read(&mut archive, package_path)?;
+    let package = parse_xml(&package)?;
+
+    let mut items = HashMap::new();
+    for node in package.descendants().filter(|node| node.has_tag_name("item")) {
+        if let (Some(id), Some(href)) = (node.attribute("id"), node.attribute("href")) {
+            items.insert(id, Item {
+                path: resolve(package_path, href),
+                media_type: node.attribute("media-type").unwrap_or_default(),
+                properties: node.attribute("properties").unwrap_or_default(),
+            });
+        }
+    }
+
+    let spine = package.descendants()
+        .find(|node| node.has_tag_name("spine"))
+        .ok_or_else(|| Error::other("The book has no reading order"))?;
+    let pages = spine.children()
+        .filter(|node| node.has_tag_name("itemref"))
+        .filter_map(|node| items.get(node.attribute("idref")?))
+        .enumerate()
+        .map(|(index, item)| (item.path.clone(), index + 1))
+        .collect::<HashMap<_, _>>();
+
+    // EPUB 3 books have a navigation document, its `toc` list is the contents
+    let navigation = items.values().find(|item| item.properties.split_whitespace().any(|property| property == "nav"));
+    if let Some(item) = navigation {
+        let text = read(&mut archive, &item.path)?;
+        let document = parse_xml(&text)?;
+
+        let list = document.descendants()
+            .find(|node| {
+                node.has_tag_name("nav")
+                    && node.attribute((OPS_NAMESPACE, "type")).unwrap_or_default().split_whitespace().any(|kind| kind == "toc")
+            })
+            .and_then(|nav| nav.children().find(|node| node.has_tag_name("ol")));
+        if let Some(list) = list {
+            return Ok(Contents::new(nav_list(list, &item.path, &pages)));
+        }
+    }
+
+    // EPUB 2 books have an NCX, named by the spine or found by its type
+    let ncx = spine.attribute("toc")
+        .and_then(|id| items.get(id))
+        .or_else(|| items.values().find(|item| item.media_type == "application/x-dtbncx+xml"));
+    match ncx {
+        Some(item) => {
+            let text = read(&mut archive, &item.path)?;
+            let document = parse_xml(&text)?;
+
+            let chapters = document.descendants()
+                .find(|node| node.has_tag_name("navMap"))
+                .map(|map| nav_points(map, &item.path, &pages))
+                .unwrap_or_default();
+
+            Ok(Contents::new(chapters))
+        }
+        None => Ok(Contents::default()),
+    }
+}
+
+// Get the chapters of an `<ol>` of the navigation document, each `<li>` with a link or a heading and maybe another list
+fn nav_list(list: Node, base: &str, pages: &HashMap<String, usize>) -> Vec<Chapter> {
+    let mut chapters = Vec::new();
+
+    for item in list.children().filter(|node| node.has_tag_name("li")) {
+        let label = item.children().find(|node| node.has_tag_name("a") || node.has_tag_name("span"));
+        let page = label.and_then(|label| label.attribute("href"))
+            .and_then(|href| pages.get(&resolve(base, href)))
+            .copied();
+        let children = item.children()
+            .find(|node| node.has_tag_name("ol"))
+            .map(|list| nav_list(list, base, pages))
+            .unwrap_or_default();
+
+        push(&mut chapters, label.map(text_of).unwrap_or_default(), page, children);
+    }
+
+    chapters
+}
+
+// Get the chapters of the `<navPoint>`s of the NCX inside `parent`
+fn nav_points(parent: Node, base: &str, pages: &HashMap<String, usize>) -> Vec<Chapter> {
+    let mut chapters = Vec::new();
+
+    for point in parent.children().filter(|node| node.has_tag_name("navPoint")) {
+        let title = point.children()
+            .find(|node| node.has_tag_name("navLabel"))
+            .map(text_of)
+            .unwrap_or_default();
+        let page = point.children()
+            .find(|node| node.has_tag_name("content"))
+            .and_then(|content| content.attribute("src"))
+            .and_then(|src| pages.get(&resolve(base, src)))
+            .copied();
+
+        push(&mut chapters, title, page, nav_points(point, base, pages));
+    }
+
+    chapters
+}
+
+fn read(archive: &mut ZipArchive<File>, name: &str) -> Result<String> {
+    let mut file = archive.by_name(name).map_err(invalid)?;
+    let mut text = String::new();
+    file.read_to_string(&mut text)?;
+
+    Ok(text)
+}
+
+// Get the path in the archive of a link found in the file at `base`, without its fragment
+fn resolve(base: &str, href: &str) -> String {
+    let href = decode(href.split('#').next().unwrap_or_default());
+    let mut parts = match base.rsplit_once('/') {
+        Some((directory, _)) => directory.split('/').collect::<Vec<_>>(),
+        None => Vec::new(),
+    };
+
+    for part in href.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+
+    parts.join("/")
+}
+
+// Decode the escaped characters of a link, like `%20`
+fn decode(href: &str) -> String {
+    let mut bytes = Vec::new();
+    let mut index = 0;
+
+    while index < href.len() {
+        let byte = href.get(index + 1..index + 3)
+            .filter(|_| href.as_bytes()[index] == b'%')
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match byte {
+            Some(byte) => {
+                bytes.push(byte);
+                index += 3;
+            }
+            None => {
+                bytes.push(href.as_bytes()[index]);
+                index += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&bytes).into_owned()
+}
@@ -0,0 +1,47 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use std::fs;
+use std::path::Path;
+
+use roxmltree::Node;
+
+use super::{parse_xml, push, text_of, Chapter, Contents};
+use crate::error::Result;
+
+
+// Read the table of contents of an FB2 from the sections of its main body; the page of a chapter is the
+// position of its section in the reading order, from 1
+pub fn from_fb2(path: &Path) -> Result<Contents> {
+    let text = fs::read_to_string(path)?;
+    let document = parse_xml(&text)?;
+
+    // The other bodies have the notes and comments
+    let body = document.root_element()
+        .children()
+        .find(|node| node.has_tag_name("body") && node.attribute("name").is_none());
+
+    let mut page = 0;
+    let chapters = body.map(|body| sections(body, &mut page)).unwrap_or_default();
+
+    Ok(Contents::new(chapters))
+}
+
+fn sections(parent: Node, page: &mut usize) -> Vec<Chapter> {
+    let mut chapters = Vec::new();
+
+    for section in parent.children().filter(|node| node.has_tag_name("section")) {
+        *page += 1;
+        let start = *page;
+
+        let title = section.children()
+            .find(|node| node.has_tag_name("title"))
+            .map(text_of)
+            .unwrap_or_default();
+        let children = sections(section, page);
+
+        push(&mut chapters, title, Some(start), children);
+    }
+
+    chapters
+}
@@ -0,0 +1,185 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use lopdf::{Dictionary, Document, Object, ObjectId};
+
+use super::{invalid, push, Chapter, Contents};
+use crate::error::Result;
+
+
+// How deep the tree of named destinations is followed
+const NAMES_DEPTH: usize = 32;
+
+// The outline of a PDF, whose items, actions and destinations can be written in place or as references;
+// read by hand since `Document::get_toc` sorts it by title and drops the items with an action written in place
+struct Outline<'a> {
+    document: &'a Document,
+    // The number of each page, from 1, by its ID
+    pages: HashMap<ObjectId, usize>,
+    // The destinations the items can link to by name
+    names: HashMap<&'a [u8], &'a Object>,
+}
+
+// Read the table of contents of a PDF from its outline, the page of a chapter is the page it links to
+pub fn from_pdf(path: &Path) -> Result<Contents> {
+    let document = Document::load(path).map_err(invalid)?;
+    let catalog = document.catalog().map_err(invalid)?;
+
+    let mut outline = Outline {
+        document: &document,
+        pages: document.get_pages()
+            .into_iter()
+            .map(|(number, id)| (id, number as usize))
+            .collect(),
+        names: HashMap::new(),
+    };
+
+    // PDF 1.1 keeps the named destinations in a dictionary, the later versions in a tree
+    if let Some(destinations) = catalog.get(b"Dests").ok().and_then(|destinations| outline.dictionary(destinations)) {
+        for (name, destination) in destinations.iter() {
+            outline.names.insert(name.as_slice(), destination);
+        }
+    }
+    let tree = catalog.get(b"Names").ok()
+        .and_then(|names| outline.dictionary(names))
+        .and_then(|names| outline.dictionary(names.get(b"Dests").ok()?));
+    if let Some(tree) = tree {
+        outline.read_names(tree, 0);
+    }
+
+    // Without an outline there are no contents
+    let first = catalog.get(b"Outlines").ok()
+        .and_then(|outlines| outline.dictionary(outlines))
+        .and_then(|outlines| outlines.get(b"First").ok());
+    let chapters = match first {
+        Some(first) => outline.chapters(first, &mut HashSet::new()),
+        None => Vec::new(),
+    };
+
+    Ok(Contents::new(chapters))
+}
+
+impl<'a> Outline<'a> {
+    // Get the items from `first` on and the ones inside them
+    fn chapters(&self, first: &'a Object, seen: &mut HashSet<ObjectId>) -> Vec<Chapter> {
+        let mut chapters = Vec::new();
+        let mut next = Some(first);
+
+        while let Some(object) = next {
+            // A broken outline can link back to an item already read
+            if let Object::Reference(id) = object {
+                if !seen.insert(*id) {
+                    break;
+                }
+            }
+            let item = match self.dictionary(object) {
+                Some(item) => item,
+                None => break,
+            };
+
+            let title = item.get(b"Title").ok()
+                .and_then(|title| self.resolve(title))
+                .map(decode)
+                .unwrap_or_default();
+            let children = match item.get(b"First") {
+                Ok(first) => self.chapters(first, seen),
+                Err(_) => Vec::new(),
+            };
+
+            push(&mut chapters, title.trim().to_string(), self.page(item), children);
+            next = item.get(b"Next").ok();
+        }
+
+        chapters
+    }
+
+    // Get the page an item links to, by its destination or its `GoTo` action
+    fn page(&self, item: &'a Dictionary) -> Option<usize> {
+        let destination = match item.get(b"Dest") {
+            Ok(destination) => destination,
+            Err(_) => {
+                let action = self.dictionary(item.get(b"A").ok()?)?;
+                if action.get(b"S").ok()?.as_name().ok()? != b"GoTo" {
+                    return None;
+                }
+
+                action.get(b"D").ok()?
+            }
+        };
+
+        // A named destination can not name another
+        let destination = match self.resolve(destination)? {
+            Object::String(name, _) | Object::Name(name) => self.resolve(self.names.get(name.as_slice())?)?,
+            destination => destination,
+        };
+        let destination = match destination {
+            Object::Dictionary(destination) => self.resolve(destination.get(b"D").ok()?)?,
+            destination => destination,
+        };
+
+        match destination.as_array().ok()?.first()? {
+            Object::Reference(id) => self.pages.get(id).copied(),
+            // Links to other files point to a page by its index
+            Object::Integer(page) => usize::try_from(*page).ok().map(|page| page + 1),
+            _ => None,
+        }
+    }
+
+    // Read a node of the tree of named destinations, its names and values in pairs
+    fn read_names(&mut self, node: &'a Dictionary, depth: usize) {
+        if depth > NAMES_DEPTH {
+            return;
+        }
+
+        let kids = node.get(b"Kids").ok().and_then(|kids| self.array(kids));
+        for kid in kids.into_iter().flatten() {
+            if let Some(kid) = self.dictionary(kid) {
+                self.read_names(kid, depth + 1);
+            }
+        }
+
+        let names = node.get(b"Names").ok().and_then(|names| self.array(names));
+        for pair in names.into_iter().flat_map(|names| names.chunks(2)) {
+            if let [Object::String(name, _), destination] = pair {
+                self.names.insert(name.as_slice(), destination);
+            }
+        }
+    }
+
+    fn resolve(&self, object: &'a Object) -> Option<&'a Object> {
+        self.document.dereference(object)
+            .ok()
+            .map(|(_, object)| object)
+    }
+
+    fn dictionary(&self, object: &'a Object) -> Option<&'a Dictionary> {
+        self.resolve(object)?.as_dict().ok()
+    }
+
+    fn array(&self, object: &'a Object) -> Option<&'a Vec<Object>> {
+        self.resolve(object)?.as_array().ok()
+    }
+}
+
+// Decode a text of the PDF, in UTF-16 or UTF-8 when it starts with their mark or else in PDFDocEncoding, which
+// matches Latin-1 in the letters
+fn decode(text: &Object) -> String {
+    let bytes = match text {
+        Object::String(bytes, _) => bytes.as_slice(),
+        _ => return String::new(),
+    };
+
+    match bytes {
+        [0xfe, 0xff, rest @ ..] => {
+            let units = rest.chunks(2)
+                .map(|pair| u16::from_be_bytes([pair[0], *pair.get(1).unwrap_or(&0)]))
+                .collect::<Vec<_>>();
+            String::from_utf16_lossy(&units)
+        }
+        [0xef, 0xbb, 0xbf, rest @ ..] => String::from_utf8_lossy(rest).into_owned(),
+        _ => bytes.iter().map(|&byte| byte as char).collect(),
+    }
+}
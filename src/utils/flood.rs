@@ -0,0 +1,46 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use std::future::Future;
+use std::time::Duration;
+
+use grammers_client::InvocationError;
+use log::warn;
+use tokio::time;
+
+use crate::config;
+
+
+// Run a request, sleeping and trying again while Telegram answers with `FLOOD_WAIT`; the waits longer
+// than `limits.max_flood_wait` are returned at once, as the updates of the chat wait with the request
+pub async fn retry<T, F, Fut>(mut request: F) -> Result<T, InvocationError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, InvocationError>>,
+{
+    let limits = config::get().limits.clone();
+    let (retries, max_wait) = (limits.flood_retries, limits.max_flood_wait);
+    let mut attempt = 0;
+
+    loop {
+        let result = request().await;
+        match result {
+            Err(InvocationError::Rpc(ref error)) if is_flood_wait(&error.name) && attempt < retries => {
+                let seconds = error.value.unwrap_or(1) as u64;
+                if seconds > max_wait {
+                    warn!("Flood wait of {} seconds is longer than {}, not trying again", seconds, max_wait);
+                    return result;
+                }
+                attempt += 1;
+
+                warn!("Flood wait of {} seconds, trying again ({}/{})", seconds, attempt, retries);
+                time::sleep(Duration::from_secs(seconds)).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+fn is_flood_wait(name: &str) -> bool {
+    name == "FLOOD_WAIT" || name == "SLOWMODE_WAIT"
+}
@@ -1,20 +1,287 @@
 // SPDX-License-Identifier: MIT
 // Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
 
+use std::ops::Range;
+
 use grammers_client::{button, reply_markup};
 
+use crate::error::{Error, Result};
+
+
+// Bytes Telegram accepts in the data of a callback button
+pub const CALLBACK_LIMIT: usize = 64;
+
+// An inline keyboard, kept apart from grammers so it can be compared in the tests
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Keyboard {
+    pub rows: Vec<Vec<Button>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Button {
+    pub text: String,
+    pub kind: ButtonKind,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ButtonKind {
+    Callback(Vec<u8>),
+    // Opens a link
+    Url(String),
+    // Starts an inline query with the bot, in the current chat or in one the user chooses
+    SwitchInline {
+        query: String,
+        current_chat: bool,
+    },
+}
+
+// The data of a callback button, written as words separated by spaces, like `translation approve 12`
+pub trait Payload: Sized {
+    fn encode(&self) -> String;
+
+    // Read the data back, `None` if it is not this payload
+    fn decode(data: &str) -> Option<Self>;
+}
+
+impl Button {
+    pub fn callback<T: Into<String>, D: Into<Vec<u8>>>(text: T, data: D) -> Self {
+        Self {
+            text: text.into(),
+            kind: ButtonKind::Callback(data.into()),
+        }
+    }
+
+    pub fn payload<T: Into<String>, P: Payload>(text: T, payload: &P) -> Self {
+        Self::callback(text, payload.encode())
+    }
+
+    pub fn url<T: Into<String>, U: Into<String>>(text: T, url: U) -> Self {
+        Self {
+            text: text.into(),
+            kind: ButtonKind::Url(url.into()),
+        }
+    }
+
+    // Start an inline query in the chat the user chooses
+    pub fn switch_inline<T: Into<String>, Q: Into<String>>(text: T, query: Q) -> Self {
+        Self {
+            text: text.into(),
+            kind: ButtonKind::SwitchInline { query: query.into(), current_chat: false },
+        }
+    }
+
+    // Start an inline query in the current chat
+    pub fn switch_inline_current<T: Into<String>, Q: Into<String>>(text: T, query: Q) -> Self {
+        Self {
+            text: text.into(),
+            kind: ButtonKind::SwitchInline { query: query.into(), current_chat: true },
+        }
+    }
+
+    // Room the button takes in a row, its text and some padding
+    fn width(&self) -> usize {
+        self.text.chars().count() + 2
+    }
+}
+
+impl Keyboard {
+    pub fn builder() -> KeyboardBuilder {
+        KeyboardBuilder::default()
+    }
+
+    // Find the button with the given text
+    pub fn button(&self, text: &str) -> Option<&Button> {
+        self.rows.iter().flatten().find(|btn| btn.text == text)
+    }
+
+    pub fn to_markup(&self) -> reply_markup::Inline {
+        let mut keyboard = Vec::new();
+
+        for line in self.rows.iter() {
+            let mut row = Vec::new();
+            for btn in line.iter() {
+                row.push(match &btn.kind {
+                    ButtonKind::Callback(data) => button::inline(btn.text.as_str(), data.as_slice()),
+                    ButtonKind::Url(url) => button::url(btn.text.as_str(), url.as_str()),
+                    ButtonKind::SwitchInline { query, current_chat: true } => button::switch(btn.text.as_str(), query.as_str()),
+                    ButtonKind::SwitchInline { query, current_chat: false } => button::switch_elsewhere(btn.text.as_str(), query.as_str()),
+                });
+            }
+            keyboard.push(row);
+        }
+
+        reply_markup::inline(keyboard)
+    }
+}
+
+// Builds a keyboard row by row, the buttons go to a new row when the current one is full
+#[derive(Clone, Debug, Default)]
+pub struct KeyboardBuilder {
+    rows: Vec<Vec<Button>>,
+    // Characters a row holds, no limit if 0
+    width: usize,
+    // Buttons a row holds, no limit if 0
+    columns: usize,
+}
+
+impl KeyboardBuilder {
+    // Wrap the rows once their texts reach `width` characters, about 30 fit on a phone
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = width;
+
+        self
+    }
+
+    // Wrap the rows once they have `columns` buttons
+    pub fn columns(mut self, columns: usize) -> Self {
+        self.columns = columns;
+
+        self
+    }
+
+    // Add a button to the current row, or to a new one if it does not fit
+    pub fn button(mut self, button: Button) -> Self {
+        let fits = match self.rows.last() {
+            Some(row) if row.is_empty() => true,
+            Some(row) => {
+                let width = row.iter().map(Button::width).sum::<usize>() + button.width();
+                (self.width == 0 || width <= self.width) && (self.columns == 0 || row.len() < self.columns)
+            }
+            None => false,
+        };
+
+        if !fits {
+            self.rows.push(Vec::new());
+        }
+        if let Some(row) = self.rows.last_mut() {
+            row.push(button);
+        }
+
+        self
+    }
+
+    pub fn buttons<I: IntoIterator<Item = Button>>(mut self, buttons: I) -> Self {
+        for button in buttons {
+            self = self.button(button);
+        }
+
+        self
+    }
+
+    // Put the next buttons in a new row
+    pub fn row(mut self) -> Self {
+        if self.rows.last().map(|row| !row.is_empty()).unwrap_or(false) {
+            self.rows.push(Vec::new());
+        }
+
+        self
+    }
+
+    // Add the buttons of a paginator in a row of their own
+    pub fn paginator(self, paginator: &Paginator) -> Self {
+        let buttons = paginator.buttons();
+        if buttons.is_empty() {
+            return self;
+        }
+
+        let mut builder = self.row();
+        builder.rows.push(buttons);
+
+        builder.row()
+    }
+
+    // Telegram refuses the whole message if the data of a button is longer than `CALLBACK_LIMIT` bytes
+    pub fn build(self) -> Result<Keyboard> {
+        for btn in self.rows.iter().flatten() {
+            if let ButtonKind::Callback(data) = &btn.kind {
+                if data.len() > CALLBACK_LIMIT {
+                    return Err(Error::other(format!("The data of the button `{}` is longer than {} bytes", btn.text, CALLBACK_LIMIT)));
+                }
+            }
+        }
+
+        Ok(Keyboard {
+            rows: self.rows
+                .into_iter()
+                .filter(|row| !row.is_empty())
+                .collect(),
+        })
+    }
+}
+
+// Buttons to move between the pages of a list, like « ‹ 3/17 › », pressing one sends `prefix page`;
+// the one showing the current page sends `prefix noop`, no handler takes it so it is only answered
+#[derive(Clone, Debug, PartialEq)]
+pub struct Paginator {
+    prefix: String,
+    // From 1 to `pages`
+    page: usize,
+    pages: usize,
+}
 
-pub fn make_keyboard(buttons: Vec<Vec<(&str, &str)>>) -> reply_markup::Inline {
-    let mut keyboard = Vec::new();
+impl Paginator {
+    pub fn new<P: Into<String>>(prefix: P, page: usize, pages: usize) -> Self {
+        let pages = pages.max(1);
 
-    let iter = buttons.iter();
-    for line in iter {
-        let mut row = Vec::new();
-        for btn in line.iter() {
-            row.push(button::inline(btn.0, btn.1.to_string().as_bytes()));
+        Self {
+            prefix: prefix.into(),
+            page: page.clamp(1, pages),
+            pages: pages,
         }
-        keyboard.push(row);
     }
 
-    reply_markup::inline(keyboard)
+    // Split `total` items in pages of `per_page`
+    pub fn for_items<P: Into<String>>(prefix: P, page: usize, total: usize, per_page: usize) -> Self {
+        let per_page = per_page.max(1);
+
+        Self::new(prefix, page, (total + per_page - 1) / per_page)
+    }
+
+    pub fn page(&self) -> usize {
+        self.page
+    }
+
+    pub fn pages(&self) -> usize {
+        self.pages
+    }
+
+    // Get the indexes of the items of the page, for lists split with `for_items`
+    pub fn range(&self, total: usize, per_page: usize) -> Range<usize> {
+        let per_page = per_page.max(1);
+        let start = ((self.page - 1) * per_page).min(total);
+
+        start..(start + per_page).min(total)
+    }
+
+    // Get the page a button of a paginator with `prefix` was pressed for
+    pub fn parse(prefix: &str, data: &str) -> Option<usize> {
+        data.strip_prefix(prefix)?
+            .strip_prefix(' ')?
+            .parse()
+            .ok()
+    }
+
+    // Get the buttons, the first and previous ones only from the second page and the next and last ones
+    // only before the last page; no buttons if there is only one page
+    pub fn buttons(&self) -> Vec<Button> {
+        if self.pages <= 1 {
+            return Vec::new();
+        }
+
+        let button = |text: String, page: usize| Button::callback(text, format!("{} {}", self.prefix, page));
+        let mut buttons = Vec::new();
+
+        if self.page > 1 {
+            buttons.push(button("«".to_string(), 1));
+            buttons.push(button("‹".to_string(), self.page - 1));
+        }
+        // Telegram refuses to edit a message without changes, so it does not send the page again
+        buttons.push(Button::callback(format!("{}/{}", self.page, self.pages), format!("{} noop", self.prefix)));
+        if self.page < self.pages {
+            buttons.push(button("›".to_string(), self.page + 1));
+            buttons.push(button("»".to_string(), self.pages));
+        }
+
+        buttons
+    }
 }
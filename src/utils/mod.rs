@@ -2,5 +2,10 @@
 // Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
 
 mod keyboard;
+pub mod flood;
+pub mod html;
+pub mod ratelimit;
+pub mod text;
 
-pub use keyboard::make_keyboard;
+pub use keyboard::{Button, ButtonKind, CALLBACK_LIMIT, Keyboard, KeyboardBuilder, Paginator, Payload};
+pub use ratelimit::RateLimiter;
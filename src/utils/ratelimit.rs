@@ -0,0 +1,79 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Mutex;
+use std::time::Instant;
+
+
+// Buckets kept before the full ones start being forgotten
+const MAX_BUCKETS: usize = 10_000;
+
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+// A token bucket per key, each action takes a token and the tokens refill over time
+pub struct RateLimiter<K> {
+    // Tokens a bucket holds when full, how many actions can be made in a burst
+    capacity: f64,
+    // Tokens given back per second
+    rate: f64,
+    buckets: Mutex<HashMap<K, Bucket>>,
+}
+
+impl<K: Eq + Hash> RateLimiter<K> {
+    pub fn new(capacity: u32, rate: f64) -> Self {
+        Self {
+            capacity: capacity as f64,
+            rate: rate,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Take a token from the bucket of `key`, returns `false` if it is empty
+    pub fn check(&self, key: K) -> bool {
+        self.take(key, true)
+    }
+
+    // Whether the bucket of `key` has a token, without taking it
+    pub fn peek(&self, key: K) -> bool {
+        self.take(key, false)
+    }
+
+    fn take(&self, key: K, consume: bool) -> bool {
+        // A limiter without capacity limits nothing
+        if self.capacity <= 0.0 {
+            return true;
+        }
+
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+
+        if buckets.len() > MAX_BUCKETS {
+            let (capacity, rate) = (self.capacity, self.rate);
+            buckets.retain(|_, bucket| bucket.tokens + now.duration_since(bucket.updated_at).as_secs_f64() * rate < capacity);
+        }
+
+        let bucket = buckets.entry(key).or_insert(Bucket {
+            tokens: self.capacity,
+            updated_at: now,
+        });
+
+        let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.rate).min(self.capacity);
+        bucket.updated_at = now;
+
+        if bucket.tokens >= 1.0 {
+            if consume {
+                bucket.tokens -= 1.0;
+            }
+
+            true
+        } else {
+            false
+        }
+    }
+}
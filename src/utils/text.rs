@@ -0,0 +1,320 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+
+// Characters Telegram accepts in a message, after the HTML is parsed
+pub const MESSAGE_LIMIT: usize = 4096;
+// Characters Telegram accepts in the caption of a media
+pub const CAPTION_LIMIT: usize = 1024;
+
+// Written at the end of a truncated text
+const ELLIPSIS: &str = "…";
+// Tags Telegram accepts in HTML texts
+const TAGS: &[&str] = &["a", "b", "blockquote", "code", "del", "em", "i", "ins", "pre", "s", "span", "strike", "strong", "tg-emoji", "tg-spoiler", "u"];
+// Named entities Telegram accepts, any other character is written as a number like `&#128214;`
+const ENTITIES: &[&str] = &["&amp;", "&gt;", "&lt;", "&quot;"];
+
+// A part of an HTML text
+#[derive(Clone, Debug)]
+enum Token<'a> {
+    Open {
+        name: String,
+        raw: &'a str,
+    },
+    Close {
+        name: String,
+        raw: &'a str,
+    },
+    // A character or an entity like `&amp;`, and how many UTF-16 units it is once parsed
+    Text {
+        raw: &'a str,
+        length: usize,
+    },
+}
+
+impl<'a> Token<'a> {
+    fn raw(&self) -> &'a str {
+        match self {
+            Self::Open { raw, .. } | Self::Close { raw, .. } | Self::Text { raw, .. } => raw,
+        }
+    }
+
+    fn is_space(&self) -> bool {
+        matches!(self, Self::Text { raw, .. } if raw.chars().all(char::is_whitespace))
+    }
+}
+
+// Where a text can be split, the later ones are preferred within the same kind
+#[derive(Clone, Copy, Eq, Ord, PartialEq, PartialOrd)]
+enum Break {
+    Space,
+    Line,
+    Paragraph,
+}
+
+// Get the length of an HTML text the way Telegram counts it, in UTF-16 units without the tags
+pub fn length(html: &str) -> usize {
+    tokenize(html)
+        .iter()
+        .map(|token| match token {
+            Token::Text { length, .. } => *length,
+            _ => 0,
+        })
+        .sum()
+}
+
+// Check that Telegram accepts an HTML text, its tags known and closed in order and `<`, `>` and `&` only
+// written as entities; returns the part that is not accepted
+pub fn validate(html: &str) -> Result<(), String> {
+    let mut open = Vec::new();
+
+    for token in tokenize(html) {
+        match token {
+            Token::Open { name, raw } => {
+                if !TAGS.contains(&name.as_str()) {
+                    return Err(raw.to_string());
+                }
+                open.push(name);
+            }
+            Token::Close { name, raw } => {
+                if open.pop().as_deref() != Some(name.as_str()) {
+                    return Err(raw.to_string());
+                }
+            }
+            Token::Text { raw, .. } => {
+                let accepted = match raw {
+                    "<" | ">" | "&" => false,
+                    _ if raw.starts_with('<') => false,
+                    _ if raw.starts_with('&') => ENTITIES.contains(&raw) || (raw.starts_with("&#") && decode_entity(raw).is_some()),
+                    _ => true,
+                };
+                if !accepted {
+                    return Err(raw.to_string());
+                }
+            }
+        }
+    }
+
+    match open.last() {
+        Some(name) => Err(format!("<{}>", name)),
+        None => Ok(()),
+    }
+}
+
+// Whether a text has tags or entities, which are shown as they are in plain texts like buttons
+pub fn has_markup(text: &str) -> bool {
+    tokenize(text)
+        .iter()
+        .any(|token| match token {
+            Token::Text { raw, .. } => raw.len() > 1 && (raw.starts_with('&') || raw.starts_with('<')),
+            _ => true,
+        })
+}
+
+// Cut an HTML text to `limit` characters, at the end of a paragraph, line or word when there is one
+// in the second half, ending it with `…` and closing the tags left open
+pub fn truncate(html: &str, limit: usize) -> String {
+    if length(html) <= limit {
+        return html.to_string();
+    }
+
+    let tokens = tokenize(html);
+    let (end, _, open) = cut(&tokens, 0, &[], limit.saturating_sub(ELLIPSIS.encode_utf16().count()).max(1));
+
+    render(&tokens[..end], &[], &open, ELLIPSIS)
+}
+
+// Split an HTML text in parts of up to `limit` characters, at the end of paragraphs, lines or words when
+// there are some in the second half of the part; the tags open at a split are closed and opened again
+pub fn split(html: &str, limit: usize) -> Vec<String> {
+    let limit = limit.max(1);
+    if length(html) <= limit && !html.trim().is_empty() {
+        return vec![html.to_string()];
+    }
+
+    let tokens = tokenize(html);
+    let mut parts = Vec::new();
+    let mut start = skip_spaces(&tokens, 0);
+    let mut open = Vec::new();
+
+    while start < tokens.len() {
+        let (end, next, next_open) = cut(&tokens, start, &open, limit);
+
+        let part = render(&tokens[start..end], &open, &next_open, "");
+        if length(&part) > 0 {
+            parts.push(part);
+        }
+
+        start = skip_spaces(&tokens, next);
+        open = next_open;
+    }
+
+    parts
+}
+
+// Find where the part starting at `start` ends, returns the end of the part, where the next one starts
+// and the tags open at the end
+fn cut<'a>(tokens: &[Token<'a>], start: usize, open: &[Token<'a>], limit: usize) -> (usize, usize, Vec<Token<'a>>) {
+    let mut stack = open.to_vec();
+    let mut length = 0;
+    // The best place to split so far, by kind: index of the space, length before it and tags open there
+    let mut breaks: Vec<(Break, usize, usize, Vec<Token<'a>>)> = Vec::new();
+    let mut previous_newline = false;
+
+    for (index, token) in tokens.iter().enumerate().skip(start) {
+        match token {
+            Token::Open { .. } => stack.push(token.clone()),
+            Token::Close { name, .. } => {
+                if let Some(position) = stack.iter().rposition(|open| matches!(open, Token::Open { name: open_name, .. } if open_name == name)) {
+                    stack.truncate(position);
+                }
+            }
+            Token::Text { raw, length: token_length } => {
+                if length + token_length > limit {
+                    // Only the splits that keep at least half of the part
+                    let best = breaks.iter()
+                        .filter(|(_, _, before, _)| *before * 2 >= limit)
+                        .max_by_key(|(kind, space, _, _)| (*kind, *space))
+                        .or_else(|| breaks.iter().filter(|(_, _, before, _)| *before > 0).max_by_key(|(_, index, _, _)| *index));
+
+                    return match best {
+                        Some((_, space, _, open)) => (*space, space + 1, open.clone()),
+                        // A single character longer than the limit goes alone
+                        None if length == 0 => (index + 1, index + 1, stack),
+                        None => (index, index, stack),
+                    };
+                }
+                length += token_length;
+
+                let kind = match *raw {
+                    "\n" if previous_newline => Some(Break::Paragraph),
+                    "\n" => Some(Break::Line),
+                    _ if token.is_space() => Some(Break::Space),
+                    _ => None,
+                };
+                previous_newline = *raw == "\n";
+
+                if let Some(kind) = kind {
+                    // The first newline of a paragraph is where it is cut
+                    let (space, before) = if kind == Break::Paragraph {
+                        breaks.iter()
+                            .rev()
+                            .find(|(kind, ..)| *kind == Break::Line)
+                            .map(|(_, space, before, _)| (*space, *before))
+                            .unwrap_or((index, length - token_length))
+                    } else {
+                        (index, length - token_length)
+                    };
+
+                    breaks.retain(|(existing, ..)| *existing != kind);
+                    breaks.push((kind, space, before, stack.clone()));
+                }
+            }
+        }
+    }
+
+    (tokens.len(), tokens.len(), stack)
+}
+
+// Write the tokens of a part, opening the tags left open by the previous part and closing the ones left open
+fn render(tokens: &[Token], open: &[Token], close: &[Token], suffix: &str) -> String {
+    // Spaces before the split are not kept
+    let end = tokens.iter()
+        .rposition(|token| !token.is_space())
+        .map(|index| index + 1)
+        .unwrap_or(0);
+
+    let mut text = String::new();
+    for token in open.iter().chain(tokens[..end].iter()) {
+        text.push_str(token.raw());
+    }
+    text.push_str(suffix);
+    for token in close.iter().rev() {
+        if let Token::Open { name, .. } = token {
+            text.push_str(&format!("</{}>", name));
+        }
+    }
+
+    text
+}
+
+fn skip_spaces(tokens: &[Token], start: usize) -> usize {
+    tokens.iter()
+        .skip(start)
+        .position(|token| !token.is_space())
+        .map(|position| start + position)
+        .unwrap_or(tokens.len())
+}
+
+fn tokenize(html: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut index = 0;
+
+    while index < html.len() {
+        let rest = &html[index..];
+        let c = rest.chars().next().unwrap();
+
+        let tag_end = if c == '<' { rest.find('>') } else { None };
+        let entity_end = if c == '&' { entity_length(rest) } else { None };
+
+        let token = if let Some(end) = tag_end {
+            let raw = &rest[..=end];
+            let inner = raw[1..raw.len() - 1].trim();
+            let name = inner.trim_start_matches('/')
+                .split(|c: char| c.is_whitespace() || c == '/')
+                .next()
+                .unwrap_or_default()
+                .to_lowercase();
+
+            if inner.starts_with('/') {
+                Token::Close { name, raw }
+            } else if inner.ends_with('/') {
+                // Nothing to close, like `<br/>`
+                Token::Text { raw, length: 0 }
+            } else {
+                Token::Open { name, raw }
+            }
+        } else if let Some(end) = entity_end {
+            let raw = &rest[..end];
+            Token::Text { raw, length: entity_units(raw) }
+        } else {
+            Token::Text { raw: &rest[..c.len_utf8()], length: c.len_utf16() }
+        };
+
+        index += token.raw().len();
+        tokens.push(token);
+    }
+
+    tokens
+}
+
+// Get the length of the entity a text starts with, like `&amp;` or `&#128214;`
+fn entity_length(text: &str) -> Option<usize> {
+    let end = text.find(';')?;
+    let name = &text[1..end];
+    let valid = !name.is_empty() && (name.chars().all(|c| c.is_ascii_alphanumeric()) || (name.starts_with('#') && name[1..].chars().all(|c| c.is_ascii_hexdigit() || c == 'x' || c == 'X')));
+
+    if valid {
+        Some(end + 1)
+    } else {
+        None
+    }
+}
+
+// Get how many UTF-16 units an entity is once parsed
+fn entity_units(entity: &str) -> usize {
+    decode_entity(entity)
+        .map(|c| c.len_utf16())
+        .unwrap_or(1)
+}
+
+// Get the character of a numeric entity, like `&#128214;` or `&#x1F4D6;`
+fn decode_entity(entity: &str) -> Option<char> {
+    let name = &entity[1..entity.len() - 1];
+    let code = match name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => name.strip_prefix('#').and_then(|decimal| decimal.parse().ok()),
+    };
+
+    code.and_then(char::from_u32)
+}
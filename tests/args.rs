@@ -0,0 +1,62 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use myne_books::handler::args::{Args, Error};
+
+
+fn parse(text: &str) -> Result<Args, Error> {
+    Args::parse(text, &["/".to_string(), "!".to_string()], Some("MyneBot"))
+}
+
+#[test]
+fn strips_the_prefix_and_the_bot_username() {
+    let args = parse("!search@mynebot dom casmurro").unwrap();
+
+    assert_eq!(args.command(), "search");
+    assert_eq!(args.len(), 2);
+    assert_eq!(args.rest(0), "dom casmurro");
+}
+
+#[test]
+fn reads_flags_and_quoted_strings() {
+    let args = parse(r#"/search --lang pt --format=epub --exact "o alienista" 'machado \'de\' assis'"#).unwrap();
+
+    assert_eq!(args.flag::<String>("lang").unwrap(), Some("pt".to_string()));
+    assert_eq!(args.flag::<String>("format").unwrap(), Some("epub".to_string()));
+    // A quoted string after a flag is its value
+    assert_eq!(args.flag::<String>("exact").unwrap(), Some("o alienista".to_string()));
+    assert_eq!(args.get::<String>(0, "author").unwrap(), "machado 'de' assis");
+    assert!(!args.has_flag("page"));
+}
+
+#[test]
+fn flags_without_value() {
+    let args = parse("/search --exact --lang pt -- --not-a-flag").unwrap();
+
+    assert!(args.has_flag("exact"));
+    assert_eq!(args.flag::<String>("exact"), Err(Error::Missing("--exact".to_string())));
+    assert_eq!(args.get::<String>(0, "query").unwrap(), "--not-a-flag");
+}
+
+#[test]
+fn typed_accessors() {
+    let args = parse("/page 3 three").unwrap();
+
+    assert_eq!(args.get::<u32>(0, "page").unwrap(), 3);
+    assert_eq!(args.get::<u32>(1, "page"), Err(Error::Invalid { name: "page".to_string(), value: "three".to_string() }));
+    assert_eq!(args.get::<u32>(2, "page"), Err(Error::Missing("page".to_string())));
+    assert_eq!(args.get_opt::<u32>(2, "page").unwrap(), None);
+}
+
+#[test]
+fn texts_that_are_not_commands() {
+    let args = parse("don't panic").unwrap();
+
+    assert_eq!(args.command(), "");
+    assert_eq!(args.rest(0), "don't panic");
+}
+
+#[test]
+fn unclosed_quotes() {
+    assert_eq!(parse(r#"/search "o alienista"#), Err(Error::UnclosedQuote));
+}
@@ -0,0 +1,54 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use myne_books::database;
+use myne_books::database::{backup, tables};
+
+
+// Use a database of its own, the records are deleted and imported again
+fn setup() {
+    let path = std::env::temp_dir().join(format!("myne_books-backup-{}.db3", std::process::id()));
+    database::set_path(&path.to_string_lossy());
+    database::connect().unwrap().initialize().unwrap();
+}
+
+#[test]
+fn exports_and_imports_every_record() {
+    setup();
+    let path = std::env::temp_dir().join(format!("myne_books-export-{}.json", std::process::id()));
+    let path = path.to_string_lossy();
+
+    tables::User::register(80, "Wilma".to_string(), Some("pt-rBR")).unwrap();
+    tables::Plugin::set("about", -1008000, false).unwrap();
+    tables::Plugin::set("translate", tables::GLOBAL, true).unwrap();
+    let approved = tables::Translation::submit("pt-rBR", "texts.start", "Olá", 80).unwrap();
+    tables::Translation::approve(approved).unwrap();
+    let pending = tables::Translation::submit("pt-rBR", "texts.about", "Sobre", 80).unwrap();
+
+    let dump = backup::export(&path).unwrap();
+    assert_eq!(dump.users.len(), 1);
+    assert_eq!(dump.plugins.len(), 2);
+    assert_eq!(dump.translations.len(), 2);
+
+    tables::User::delete(80).unwrap();
+    tables::Plugin::delete("about", -1008000).unwrap();
+    tables::Plugin::delete("translate", tables::GLOBAL).unwrap();
+    tables::Translation::delete(approved).unwrap();
+    tables::Translation::delete(pending).unwrap();
+
+    backup::import(&path).unwrap();
+    std::fs::remove_file(path.as_ref()).ok();
+
+    assert_eq!(tables::User::get(80).unwrap().language, "pt-rBR");
+    let mut plugins = tables::Plugin::all().unwrap()
+        .into_iter()
+        .map(|plugin| (plugin.name, plugin.chat_id, plugin.enabled))
+        .collect::<Vec<_>>();
+    plugins.sort();
+    assert_eq!(plugins, vec![("about".to_string(), -1008000, false), ("translate".to_string(), tables::GLOBAL, true)]);
+
+    // With the same IDs, so the pending ones can still be approved
+    let translation = tables::Translation::get(approved).unwrap();
+    assert_eq!((translation.text.as_str(), translation.approved), ("Olá", true));
+    assert_eq!(tables::Translation::pending().unwrap().iter().map(|translation| translation.id).collect::<Vec<_>>(), vec![pending]);
+}
@@ -0,0 +1,25 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use myne_books::config::{self, Config};
+
+
+// Get the problems of the plugins in a configuration
+fn problems(config: &Config) -> Vec<String> {
+    match config.validate() {
+        Err(config::Error::Invalid(problems)) => problems.into_iter().filter(|problem| problem.contains("plugins.")).collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[test]
+fn protected_plugins_can_not_be_disabled() {
+    let mut config = Config::default();
+
+    config.plugins.disabled = vec!["about".to_string()];
+    assert!(problems(&config).is_empty());
+
+    // Nothing could enable the plugins again
+    config.plugins.disabled.push("plugins".to_string());
+    assert_eq!(problems(&config), vec!["`plugins.disabled` can not have `plugins`, that plugin can not be disabled"]);
+}
@@ -0,0 +1,31 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use std::thread;
+use std::time::Duration;
+
+use myne_books::config;
+use myne_books::handler::conversation;
+
+
+#[test]
+fn unanswered_conversations_expire() {
+    let mut decoded = (*config::get()).clone();
+    decoded.limits.conversation_timeout = 1;
+    config::set(decoded);
+
+    conversation::start(1, "translate", "pt-rBR texts.start");
+    assert_eq!(conversation::get(1, "translate").as_deref(), Some("pt-rBR texts.start"));
+    assert_eq!(conversation::get(1, "other"), None);
+    assert_eq!(conversation::count(), 1);
+
+    thread::sleep(Duration::from_millis(1100));
+
+    // The update of another user is enough to forget it
+    assert_eq!(conversation::get(2, "translate"), None);
+    assert_eq!(conversation::count(), 0);
+
+    conversation::start(2, "translate", "pt-rBR texts.about");
+    conversation::finish(2);
+    assert_eq!(conversation::count(), 0);
+}
@@ -0,0 +1,93 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use std::sync::{Arc, Once};
+use std::time::Duration;
+
+use myne_books::api::mock::{self, MockApi, Request};
+use myne_books::api::{self, Chat};
+use myne_books::config;
+use myne_books::database;
+use myne_books::dispatcher::Dispatcher;
+use myne_books::handler::{self, Handler};
+
+
+static SETUP: Once = Once::new();
+
+// Use a database of its own and get the handlers of every plugin
+fn setup() -> Vec<Handler<'static>> {
+    SETUP.call_once(|| {
+        let path = std::env::temp_dir().join(format!("myne_books-dispatcher-{}.db3", std::process::id()));
+        database::set_path(&path.to_string_lossy());
+        database::connect().unwrap().initialize().unwrap();
+    });
+
+    let mut handler_list = Vec::new();
+    handler::initialize(&mut handler_list).unwrap();
+
+    handler_list
+}
+
+fn dispatcher(client: &Arc<MockApi>, queue: usize) -> Dispatcher {
+    // Without rate limits, so only the queue drops updates
+    let limits = config::Limits {
+        user_burst: 0,
+        chat_burst: 0,
+        queue: queue,
+        ..Default::default()
+    };
+
+    Dispatcher::new(client.clone(), setup(), vec!["/".to_string()], api::User::new(1000, "Myne"), &limits)
+}
+
+// Get the messages edited and how many callbacks were answered
+fn edits_and_answers(client: &MockApi) -> (Vec<Option<i32>>, usize) {
+    let requests = client.take_requests();
+    let edits = requests.iter()
+        .filter_map(|request| match request {
+            Request::EditMessage { message_id, .. } => Some(*message_id),
+            _ => None,
+        })
+        .collect();
+    let answers = requests.iter()
+        .filter(|request| matches!(request, Request::AnswerCallback { .. }))
+        .count();
+
+    (edits, answers)
+}
+
+#[tokio::test]
+async fn full_queues_drop_the_updates() {
+    let client = Arc::new(MockApi::new());
+    let group = Chat::Group(api::Group::new(700, "Archive"));
+    let mut dispatcher = dispatcher(&client, 2);
+
+    // Nothing is handled before the test waits, so the queue of the chat fills with the first two presses
+    for (message_id, user_id) in [(71, 71), (72, 72), (73, 73)] {
+        dispatcher.dispatch(mock::callback(message_id, group.clone(), api::User::new(user_id, "Dirk"), "about"));
+    }
+    assert!(dispatcher.shutdown(Duration::from_secs(5)).await);
+
+    // The dropped press is still answered, so its loading animation stops
+    let (edits, answers) = edits_and_answers(&client);
+    assert_eq!(edits, vec![Some(71), Some(72)]);
+    assert_eq!(answers, 3);
+}
+
+#[tokio::test]
+async fn repeated_presses_are_coalesced_per_message() {
+    let client = Arc::new(MockApi::new());
+    let user = api::User::new(74, "Damuel");
+    let chat = Chat::User(user.clone());
+    let mut dispatcher = dispatcher(&client, 8);
+
+    // Pressed twice on the first message and once on another one with the same button
+    for message_id in [74, 74, 75] {
+        dispatcher.dispatch(mock::callback(message_id, chat.clone(), user.clone(), "about"));
+    }
+    assert!(dispatcher.shutdown(Duration::from_secs(5)).await);
+
+    let (edits, answers) = edits_and_answers(&client);
+    assert_eq!(edits, vec![Some(74), Some(75)]);
+    assert_eq!(answers, 3);
+}
@@ -0,0 +1,144 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use std::sync::Once;
+
+use macro_rules_attribute::macro_rules_attribute;
+
+use myne_books::api::mock::{self, MockApi, Request};
+use myne_books::api::{self, Chat, OutgoingMessage};
+use myne_books::database;
+use myne_books::dyn_async;
+use myne_books::error::Result;
+use myne_books::handler::{self, Data, Filter, Handler, HandlerOptions, Register};
+use myne_books::plugins;
+
+
+static SETUP: Once = Once::new();
+
+async fn reply(data: &Data<'_>, text: &str) -> Result<()> {
+    let message = data.message.unwrap();
+    data.client.send_message(message.chat.pack(), OutgoingMessage::text(text)).await?;
+
+    Ok(())
+}
+
+#[macro_rules_attribute(dyn_async!)]
+async fn low<'fut>(data: &'fut Data) -> Result<()> {
+    reply(data, "low").await
+}
+
+#[macro_rules_attribute(dyn_async!)]
+async fn high<'fut>(data: &'fut Data) -> Result<()> {
+    reply(data, "high").await
+}
+
+#[macro_rules_attribute(dyn_async!)]
+async fn private<'fut>(data: &'fut Data) -> Result<()> {
+    reply(data, "private").await
+}
+
+#[macro_rules_attribute(dyn_async!)]
+async fn admin<'fut>(data: &'fut Data) -> Result<()> {
+    reply(data, "admin").await
+}
+
+#[macro_rules_attribute(dyn_async!)]
+async fn replied<'fut>(data: &'fut Data) -> Result<()> {
+    reply(data, "replied").await
+}
+
+fn command<'a>(priority: i32, stop: bool, filters: &'a [Filter<'a>]) -> Option<HandlerOptions<'a>> {
+    Some(HandlerOptions { is_command: true, priority: priority, stop: stop, filters: filters, ..Default::default() })
+}
+
+fn setup() -> Vec<Handler<'static>> {
+    SETUP.call_once(|| {
+        let path = std::env::temp_dir().join(format!("myne_books-test-{}.db3", std::process::id()));
+        database::set_path(&path.to_string_lossy());
+        database::connect().unwrap().initialize().unwrap();
+    });
+
+    plugins::register("filters", true);
+
+    let mut handler_list = Register::new()
+        .set_name("filters")
+        .set_enabled(true)
+        .append("message", low, "echo$", command(0, false, &[]))
+        .append("message", high, "echo$", command(10, false, &[]))
+        .append("message", low, "stop$", command(0, false, &[]))
+        .append("message", high, "stop$", command(5, true, &[]))
+        .append("message", private, "where$", command(0, false, &[Filter::Private]))
+        .append("message", admin, "where$", command(0, false, &[Filter::Group, Filter::Admin]))
+        .append("message", replied, "^[^/]", Some(HandlerOptions { filters: &[Filter::ReplyToBot], ..Default::default() }))
+        .build()
+        .get_handler_list();
+    handler_list.sort_by_key(|handler| -handler.get_priority());
+
+    handler_list
+}
+
+fn me() -> api::User {
+    let mut me = api::User::new(1000, "Myne");
+    me.username = Some("MyneBot".to_string());
+
+    me
+}
+
+// Handle an update and get the texts sent back
+async fn handle(client: &MockApi, update: api::Update) -> Vec<String> {
+    let handler_list = setup();
+    let prefixes = vec!["/".to_string()];
+
+    handler::handle_update(client, update, &handler_list, &prefixes, &me()).await.unwrap();
+
+    client.take_requests()
+        .into_iter()
+        .filter_map(|request| match request {
+            Request::SendMessage { message, .. } => Some(message.text),
+            _ => None,
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn higher_priorities_run_first() {
+    let client = MockApi::new();
+    let user = api::User::new(1, "Rozemyne");
+
+    assert_eq!(handle(&client, mock::private_message(1, user, "/echo")).await, vec!["high", "low"]);
+}
+
+#[tokio::test]
+async fn stop_skips_the_next_handlers() {
+    let client = MockApi::new();
+    let user = api::User::new(2, "Ferdinand");
+
+    assert_eq!(handle(&client, mock::private_message(2, user, "/stop")).await, vec!["high"]);
+}
+
+#[tokio::test]
+async fn chat_and_admin_filters() {
+    let client = MockApi::new().with_admin(3);
+    let admin = api::User::new(3, "Sylvester");
+    let member = api::User::new(4, "Damuel");
+    let group = Chat::Group(api::Group::new(300, "Ehrenfest"));
+
+    assert_eq!(handle(&client, mock::private_message(3, admin.clone(), "/where")).await, vec!["private"]);
+    assert_eq!(handle(&client, mock::group_message(4, group.clone(), admin, "/where")).await, vec!["admin"]);
+    assert!(handle(&client, mock::group_message(5, group, member, "/where")).await.is_empty());
+}
+
+#[tokio::test]
+async fn reply_to_bot_filter() {
+    let client = MockApi::new();
+    let user = api::User::new(5, "Brigitte");
+
+    assert_eq!(handle(&client, mock::private_message(6, user.clone(), "/echo")).await.len(), 2);
+
+    let mut message = mock::message(7, Chat::User(user.clone()), user.clone(), "hello");
+    assert!(handle(&client, api::Update::NewMessage(message.clone())).await.is_empty());
+
+    message.reply_to_message_id = Some(1);
+    assert_eq!(handle(&client, api::Update::NewMessage(message)).await, vec!["replied"]);
+}
@@ -0,0 +1,480 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use std::sync::Once;
+
+use myne_books::api::mock::{self, MockApi, Request};
+use myne_books::api::{self, Action, CallbackAnswer, Chat};
+use myne_books::config;
+use myne_books::database;
+use myne_books::handler::{self, Handler};
+use myne_books::language;
+use myne_books::plugins;
+use myne_books::utils::ButtonKind;
+
+
+static SETUP: Once = Once::new();
+
+// Use a database of its own and get the handlers of every plugin
+fn setup() -> Vec<Handler<'static>> {
+    SETUP.call_once(|| {
+        let path = std::env::temp_dir().join(format!("myne_books-test-{}.db3", std::process::id()));
+        database::set_path(&path.to_string_lossy());
+        database::connect().unwrap().initialize().unwrap();
+    });
+
+    let mut handler_list = Vec::new();
+    handler::initialize(&mut handler_list).unwrap();
+
+    handler_list
+}
+
+// The same for every test, as they share the configuration; every test has its own users, as they share
+// the database too
+fn set_owners() {
+    let mut decoded = (*config::get()).clone();
+    decoded.admins.owners = vec![7, 9, 16, 17, 19];
+    decoded.admins.translators = vec![18];
+    config::set(decoded);
+}
+
+fn me() -> api::User {
+    let mut me = api::User::new(1000, "Myne");
+    me.username = Some("MyneBot".to_string());
+
+    me
+}
+
+async fn handle(client: &MockApi, update: api::Update) {
+    let handler_list = setup();
+    let prefixes = vec!["/".to_string(), "!".to_string()];
+
+    handler::handle_update(client, update, &handler_list, &prefixes, &me()).await.unwrap();
+}
+
+#[tokio::test]
+async fn start_replies_with_the_about_button() {
+    let client = MockApi::new();
+    let user = api::User::new(1, "Rozemyne");
+
+    handle(&client, mock::private_message(10, user, "/start")).await;
+
+    let requests = client.take_requests();
+    assert_eq!(requests.len(), 1);
+    match &requests[0] {
+        Request::SendMessage { chat_id, message } => {
+            assert_eq!(*chat_id, 1);
+            assert_eq!(message.reply_to, Some(10));
+            assert!(message.html);
+            assert!(message.text.contains("@MyneBot"));
+
+            let keyboard = message.keyboard.as_ref().unwrap();
+            let button = keyboard.button("About 👥").unwrap();
+            assert_eq!(button.kind, ButtonKind::Callback(b"about".to_vec()));
+        }
+        request => panic!("Unexpected request {:?}", request),
+    }
+}
+
+#[tokio::test]
+async fn about_accepts_the_bot_username_in_groups() {
+    let client = MockApi::new().with_members_count(3);
+    let user = api::User::new(2, "Ferdinand");
+    let group = Chat::Group(api::Group::new(500, "Library"));
+
+    handle(&client, mock::group_message(20, group, user, "!about@MyneBot")).await;
+
+    match client.take_requests().as_slice() {
+        [Request::SendMessage { chat_id, message }] => {
+            assert_eq!(*chat_id, 500);
+            assert!(message.text.contains("<b>Myne</b>"));
+
+            let keyboard = message.keyboard.as_ref().unwrap();
+            let button = keyboard.button("Back 🔙").unwrap();
+            assert_eq!(button.kind, ButtonKind::Callback(b"start".to_vec()));
+        }
+        requests => panic!("Unexpected requests {:?}", requests),
+    }
+}
+
+#[tokio::test]
+async fn commands_accept_the_bot_username_in_any_case() {
+    let client = MockApi::new().with_members_count(3);
+    let user = api::User::new(21, "Sieglinde");
+    let group = Chat::Group(api::Group::new(510, "Ehrenfest"));
+
+    handle(&client, mock::group_message(210, group.clone(), user.clone(), "/about@myneBOT")).await;
+    assert!(matches!(client.take_requests().as_slice(), [Request::SendMessage { chat_id: 510, .. }]));
+
+    // Commands for another bot are still ignored
+    handle(&client, mock::group_message(211, group, user, "/about@OtherBot")).await;
+    assert!(client.take_requests().is_empty());
+}
+
+#[tokio::test]
+async fn about_callback_edits_the_message_and_answers() {
+    let client = MockApi::new();
+    let user = api::User::new(3, "Tuuli");
+
+    handle(&client, mock::callback(30, Chat::User(user.clone()), user, "about")).await;
+
+    match client.take_requests().as_slice() {
+        [Request::EditMessage { chat_id, message_id, message }, Request::AnswerCallback { answer, .. }] => {
+            assert_eq!(*chat_id, 3);
+            assert_eq!(*message_id, Some(30));
+            assert!(message.keyboard.as_ref().unwrap().button("Back 🔙").is_some());
+            assert_eq!(*answer, CallbackAnswer::default());
+        }
+        requests => panic!("Unexpected requests {:?}", requests),
+    }
+}
+
+#[tokio::test]
+async fn unknown_callbacks_are_still_answered() {
+    let client = MockApi::new();
+    let user = api::User::new(4, "Lutz");
+
+    handle(&client, mock::callback(40, Chat::User(user.clone()), user, "nothing")).await;
+
+    assert_eq!(client.take_requests(), vec![Request::AnswerCallback { chat_id: 4, answer: CallbackAnswer::default() }]);
+}
+
+#[tokio::test]
+async fn the_current_page_button_is_only_answered() {
+    let client = MockApi::new();
+    let user = api::User::new(23, "Gunther");
+
+    // The message is not edited with the page it already shows
+    handle(&client, mock::callback(230, Chat::User(user.clone()), user, "contents 7 22 noop")).await;
+
+    assert_eq!(client.take_requests(), vec![Request::AnswerCallback { chat_id: 23, answer: CallbackAnswer::default() }]);
+}
+
+#[tokio::test]
+async fn ignores_other_texts_and_prefixes() {
+    let client = MockApi::new();
+    let user = api::User::new(5, "Benno");
+
+    handle(&client, mock::private_message(50, user.clone(), "start")).await;
+    handle(&client, mock::private_message(51, user.clone(), ".start")).await;
+    handle(&client, mock::private_message(52, user, "/start@OtherBot")).await;
+
+    assert!(client.requests().is_empty());
+}
+
+#[tokio::test]
+async fn backup_is_only_for_owners() {
+    let client = MockApi::new();
+    let user = api::User::new(6, "Sylvester");
+
+    handle(&client, mock::private_message(60, user, "/backup")).await;
+
+    assert!(client.requests().is_empty());
+}
+
+#[tokio::test]
+async fn owners_can_disable_a_plugin_in_a_group() {
+    set_owners();
+
+    let client = MockApi::new();
+    let owner = api::User::new(7, "Karstedt");
+    let group = Chat::Group(api::Group::new(700, "Knights"));
+    let other_group = Chat::Group(api::Group::new(701, "Temple"));
+
+    handle(&client, mock::group_message(70, group.clone(), owner.clone(), "/disable about")).await;
+    match client.take_requests().as_slice() {
+        [Request::SendMessage { message, .. }] => assert!(message.text.contains("<code>about</code>")),
+        requests => panic!("Unexpected requests {:?}", requests),
+    }
+
+    handle(&client, mock::group_message(71, group, owner.clone(), "/about")).await;
+    assert!(client.take_requests().is_empty());
+
+    handle(&client, mock::group_message(72, other_group, owner, "/about")).await;
+    assert_eq!(client.take_requests().len(), 1);
+}
+
+#[tokio::test]
+async fn names_are_escaped_once() {
+    set_owners();
+
+    let client = MockApi::new();
+    let owner = api::User::new(16, "Eckhart");
+    let group = Chat::Group(api::Group::new(702, "Knights"));
+
+    handle(&client, mock::group_message(73, group, owner.clone(), r#"/enable "<script>&amp;""#)).await;
+    match client.take_requests().as_slice() {
+        [Request::SendMessage { message, .. }] => assert!(message.text.contains("<code>&lt;script&gt;&amp;amp;</code>")),
+        requests => panic!("Unexpected requests {:?}", requests),
+    }
+
+    // The name of the bot is escaped too
+    let mut me = me();
+    me.first_name = "<Myne & Co>".to_string();
+    let prefixes = vec!["/".to_string()];
+    handler::handle_update(&client, mock::private_message(74, owner, "/about"), &setup(), &prefixes, &me).await.unwrap();
+    match client.take_requests().as_slice() {
+        [Request::SendMessage { message, .. }] => assert!(message.text.contains("<b>&lt;Myne &amp; Co&gt;</b>")),
+        requests => panic!("Unexpected requests {:?}", requests),
+    }
+}
+
+#[tokio::test]
+async fn owners_reload_the_locales() {
+    set_owners();
+
+    let client = MockApi::new();
+    let user = api::User::new(12, "Damuel");
+    let owner = api::User::new(9, "Ferdinand");
+
+    handle(&client, mock::private_message(75, user, "/reload_locales")).await;
+    assert!(client.take_requests().is_empty());
+
+    // No directory is configured in the tests
+    handle(&client, mock::private_message(76, owner, "/reload_locales")).await;
+    match client.take_requests().as_slice() {
+        [Request::SendMessage { message, .. }] => assert!(message.text.contains("<code>locales.directory</code>")),
+        requests => panic!("Unexpected requests {:?}", requests),
+    }
+}
+
+#[tokio::test]
+async fn edited_commands_edit_the_previous_reply() {
+    let client = MockApi::new();
+    let user = api::User::new(8, "Hartmut");
+
+    handle(&client, mock::private_message(80, user.clone(), "/about")).await;
+    let reply_id = match client.take_requests().as_slice() {
+        [Request::SendMessage { .. }] => 1,
+        requests => panic!("Unexpected requests {:?}", requests),
+    };
+
+    let edited = mock::message(80, Chat::User(user.clone()), user, "/start");
+    handle(&client, api::Update::MessageEdited(edited)).await;
+
+    match client.take_requests().as_slice() {
+        [Request::EditMessage { chat_id, message_id, message }] => {
+            assert_eq!(*chat_id, 8);
+            assert_eq!(*message_id, Some(reply_id));
+            assert!(message.text.contains("@MyneBot"));
+        }
+        requests => panic!("Unexpected requests {:?}", requests),
+    }
+}
+
+#[tokio::test]
+async fn edited_messages_only_run_the_handlers_that_accept_them() {
+    set_owners();
+
+    let client = MockApi::new();
+    let owner = api::User::new(17, "Elvira");
+
+    let edited = mock::message(90, Chat::User(owner.clone()), owner, "/plugins");
+    handle(&client, api::Update::MessageEdited(edited)).await;
+
+    assert!(client.requests().is_empty());
+}
+
+#[tokio::test]
+async fn channel_posts_are_handled() {
+    let client = MockApi::new();
+    let channel = api::Channel::new(900, "Royal Library");
+    let mut post = mock::message(91, Chat::Channel(channel), api::User::new(10, "Solange"), "/about");
+    post.sender = None;
+
+    handle(&client, api::Update::NewMessage(post)).await;
+
+    match client.take_requests().as_slice() {
+        [Request::SendMessage { chat_id, .. }] => assert_eq!(*chat_id, 900),
+        requests => panic!("Unexpected requests {:?}", requests),
+    }
+    assert_eq!(database::tables::Channel::get(900).unwrap().title, "Royal Library");
+}
+
+#[tokio::test]
+async fn tracks_the_groups_the_bot_is_in() {
+    let client = MockApi::new();
+    let user = api::User::new(11, "Wilfried");
+    let group = Chat::Group(api::Group::new(1100, "<Ehrenfest>"));
+
+    handle(&client, mock::service_message(110, group.clone(), user.clone(), Action::MembersAdded(vec![1000]))).await;
+    match client.take_requests().as_slice() {
+        [Request::SendMessage { chat_id, message }] => {
+            assert_eq!(*chat_id, 1100);
+            assert!(message.text.contains("<b>&lt;Ehrenfest&gt;</b>"));
+            assert!(message.text.contains("/start@MyneBot"));
+        }
+        requests => panic!("Unexpected requests {:?}", requests),
+    }
+    assert!(database::tables::Group::get(1100).unwrap().active);
+
+    // Other members leaving changes nothing
+    handle(&client, mock::service_message(111, group.clone(), user.clone(), Action::MemberLeft(12))).await;
+    assert!(database::tables::Group::get(1100).unwrap().active);
+
+    handle(&client, mock::service_message(112, group, user, Action::MemberLeft(1000))).await;
+    assert!(!database::tables::Group::get(1100).unwrap().active);
+    assert!(client.requests().is_empty());
+}
+
+#[tokio::test]
+async fn supergroups_removing_the_bot() {
+    let client = MockApi::new();
+    let user = api::User::new(13, "Charlotte");
+    let group = Chat::Group(api::Group::new(1300, "Castle"));
+
+    handle(&client, mock::group_message(130, group, user, "hello")).await;
+    assert!(database::tables::Group::get(1300).unwrap().active);
+
+    handle(&client, api::Update::ParticipantLeft { chat_id: 1300, user_id: 1000 }).await;
+    assert!(!database::tables::Group::get(1300).unwrap().active);
+}
+
+#[tokio::test]
+async fn migrated_groups_keep_their_settings() {
+    let client = MockApi::new();
+    let user = api::User::new(14, "Angelica");
+    let group = Chat::Group(api::Group::new(1400, "Knights"));
+
+    handle(&client, mock::group_message(140, group.clone(), user.clone(), "hello")).await;
+    database::tables::Group::set_language(1400, "pt-rBR").unwrap();
+    plugins::set("about", Some(1400), false).unwrap();
+
+    handle(&client, mock::service_message(141, group, user, Action::MigratedTo(1401))).await;
+
+    assert!(database::tables::Group::get(1400).is_err());
+    assert_eq!(database::tables::Group::get(1401).unwrap().language, "pt-rBR");
+    assert!(!plugins::is_enabled("about", Some(1401)));
+    assert!(!database::tables::Plugin::all().unwrap().iter().any(|plugin| plugin.chat_id == 1400));
+}
+
+#[tokio::test]
+async fn translators_submit_and_owners_approve() {
+    set_owners();
+
+    let client = MockApi::new();
+    let mut translator = api::User::new(18, "Philine");
+    translator.lang_code = Some("de".to_string());
+    let owner = api::User::new(19, "Bonifatius");
+
+    // Other users can not translate
+    handle(&client, mock::private_message(180, api::User::new(20, "Giebe"), "/translate")).await;
+    assert!(client.take_requests().is_empty());
+
+    // The language of the translator is used, and the keys come in order
+    handle(&client, mock::private_message(181, translator.clone(), "/translate")).await;
+    match client.take_requests().as_slice() {
+        [Request::SendMessage { message, .. }] => {
+            assert!(message.text.contains("<code>de</code>"));
+            assert!(message.text.contains("<code>buttons.about</code>"));
+        }
+        requests => panic!("Unexpected requests {:?}", requests),
+    }
+
+    handle(&client, mock::private_message(182, translator.clone(), "Über {bot_name}")).await;
+    match client.take_requests().as_slice() {
+        [Request::SendMessage { message, .. }] => assert!(message.text.contains("—")),
+        requests => panic!("Unexpected requests {:?}", requests),
+    }
+
+    // Buttons are plain text
+    handle(&client, mock::private_message(183, translator.clone(), "Über <b>uns</b>")).await;
+    match client.take_requests().as_slice() {
+        [Request::SendMessage { message, .. }] => assert!(message.text.contains("without formatting")),
+        requests => panic!("Unexpected requests {:?}", requests),
+    }
+
+    handle(&client, mock::private_message(189, translator.clone(), "Über & uns")).await;
+    match client.take_requests().as_slice() {
+        [Request::SendMessage { message: submitted, .. }, Request::SendMessage { message: next, .. }] => {
+            assert!(submitted.text.contains("<code>buttons.about</code>"));
+            assert!(next.text.contains("<code>buttons.approve</code>"));
+        }
+        requests => panic!("Unexpected requests {:?}", requests),
+    }
+
+    handle(&client, mock::callback(184, Chat::User(translator.clone()), translator.clone(), "translate skip")).await;
+    match client.take_requests().as_slice() {
+        [Request::EditMessage { message, .. }, Request::AnswerCallback { .. }] => assert!(message.text.contains("<code>buttons.back</code>")),
+        requests => panic!("Unexpected requests {:?}", requests),
+    }
+
+    handle(&client, mock::callback(185, Chat::User(translator.clone()), translator.clone(), "translate cancel")).await;
+    client.take_requests();
+    handle(&client, mock::private_message(186, translator.clone(), "Zurück")).await;
+    assert!(client.take_requests().is_empty());
+
+    // The owners see the submitted text escaped
+    handle(&client, mock::private_message(187, owner.clone(), "/translations")).await;
+    match client.take_requests().as_slice() {
+        [Request::SendMessage { message, .. }] => assert!(message.text.contains("<pre>Über &amp; uns</pre>")),
+        requests => panic!("Unexpected requests {:?}", requests),
+    }
+
+    let id = database::tables::Translation::pending().unwrap()[0].id;
+    handle(&client, mock::callback(188, Chat::User(owner.clone()), owner, &format!("translation approve {}", id))).await;
+    match client.take_requests().as_slice() {
+        [Request::AnswerCallback { .. }, Request::EditMessage { .. }] => {}
+        requests => panic!("Unexpected requests {:?}", requests),
+    }
+
+    let german = language::new().get_language("de");
+    assert_eq!(german.get_text("buttons.about", vec![]), "Über & uns");
+    assert_eq!(language::find("DE"), Some("de"));
+    assert!(!language::untranslated("de").contains(&"buttons.about"));
+}
+
+#[tokio::test]
+async fn users_choose_the_languages_of_the_books() {
+    let client = MockApi::new();
+    let mut user = api::User::new(15, "Tuuli");
+    user.lang_code = Some("pt-br".to_string());
+
+    handle(&client, mock::private_message(150, user.clone(), "/languages en es")).await;
+    match client.take_requests().as_slice() {
+        [Request::SendMessage { message, .. }] => assert!(message.text.contains("<code>pt</code>, <code>en</code>, <code>es</code>")),
+        requests => panic!("Unexpected requests {:?}", requests),
+    }
+    assert_eq!(database::tables::User::get(15).unwrap().secondary_languages, vec!["en", "es"]);
+
+    handle(&client, mock::callback(151, Chat::User(user.clone()), user.clone(), "languages all")).await;
+    assert!(!database::tables::User::get(15).unwrap().show_all_languages);
+
+    handle(&client, mock::private_message(152, user, "/languages none")).await;
+    assert!(database::tables::User::get(15).unwrap().secondary_languages.is_empty());
+}
+
+// Get the texts of the buttons choosing the languages, in the order they are shown
+fn language_buttons(message: &api::OutgoingMessage) -> Vec<String> {
+    message.keyboard.as_ref().unwrap().rows.iter()
+        .flatten()
+        .filter(|button| matches!(&button.kind, ButtonKind::Callback(data) if data.starts_with(b"languages toggle ")))
+        .map(|button| button.text.clone())
+        .collect()
+}
+
+#[tokio::test]
+async fn the_languages_of_the_user_are_listed_first() {
+    let client = MockApi::new();
+    let mut user = api::User::new(22, "Hartmut");
+    user.lang_code = Some("pt-br".to_string());
+
+    // Portuguese comes first, even though English is the first bundled locale
+    handle(&client, mock::private_message(220, user.clone(), "/languages")).await;
+    match client.take_requests().as_slice() {
+        [Request::SendMessage { message, .. }] => assert_eq!(language_buttons(message)[..2], ["✓ pt", "en"]),
+        requests => panic!("Unexpected requests {:?}", requests),
+    }
+
+    handle(&client, mock::callback(221, Chat::User(user.clone()), user.clone(), "languages toggle en")).await;
+    assert_eq!(database::tables::User::get(22).unwrap().secondary_languages, vec!["en"]);
+    match client.take_requests().as_slice() {
+        [Request::EditMessage { message, .. }, Request::AnswerCallback { .. }] => assert_eq!(language_buttons(message)[..2], ["✓ pt", "✓ en"]),
+        requests => panic!("Unexpected requests {:?}", requests),
+    }
+
+    // The language of the user can not be removed
+    handle(&client, mock::callback(222, Chat::User(user.clone()), user, "languages toggle pt")).await;
+    assert_eq!(database::tables::User::get(22).unwrap().language, "pt-rBR");
+    assert_eq!(database::tables::User::get(22).unwrap().secondary_languages, vec!["en"]);
+}
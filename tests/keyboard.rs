@@ -0,0 +1,109 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use myne_books::utils::{Button, ButtonKind, Keyboard, Paginator, Payload};
+
+
+fn texts(keyboard: &Keyboard) -> Vec<Vec<&str>> {
+    keyboard.rows.iter()
+        .map(|row| row.iter().map(|button| button.text.as_str()).collect())
+        .collect()
+}
+
+#[derive(Debug, PartialEq)]
+struct Shelf {
+    id: i64,
+    page: usize,
+}
+
+impl Payload for Shelf {
+    fn encode(&self) -> String {
+        format!("shelf {} {}", self.id, self.page)
+    }
+
+    fn decode(data: &str) -> Option<Self> {
+        let mut words = data.split(' ');
+        if words.next()? != "shelf" {
+            return None;
+        }
+
+        Some(Self {
+            id: words.next()?.parse().ok()?,
+            page: words.next()?.parse().ok()?,
+        })
+    }
+}
+
+#[test]
+fn wraps_rows_by_width_and_columns() {
+    let keyboard = Keyboard::builder()
+        .width(24)
+        .buttons(["Dom Casmurro", "Emma", "Don Quijote", "Iracema"].iter().map(|title| Button::callback(*title, *title)))
+        .build()
+        .unwrap();
+    assert_eq!(texts(&keyboard), vec![vec!["Dom Casmurro", "Emma"], vec!["Don Quijote", "Iracema"]]);
+
+    let keyboard = Keyboard::builder()
+        .columns(3)
+        .buttons((1..=5).map(|number| Button::callback(number.to_string(), number.to_string())))
+        .row()
+        .button(Button::callback("Back", "start"))
+        .build()
+        .unwrap();
+    assert_eq!(texts(&keyboard), vec![vec!["1", "2", "3"], vec!["4", "5"], vec!["Back"]]);
+}
+
+#[test]
+fn builds_every_kind_of_button() {
+    let keyboard = Keyboard::builder()
+        .button(Button::url("Source", "https://github.com/AndrielFR/MyneBooks"))
+        .button(Button::switch_inline("Share", "dom casmurro"))
+        .button(Button::switch_inline_current("Search", ""))
+        .row()
+        .button(Button::payload("Shelf", &Shelf { id: 7, page: 2 }))
+        .build()
+        .unwrap();
+
+    assert_eq!(keyboard.rows.len(), 2);
+    assert_eq!(keyboard.button("Source").unwrap().kind, ButtonKind::Url("https://github.com/AndrielFR/MyneBooks".to_string()));
+    assert_eq!(keyboard.button("Share").unwrap().kind, ButtonKind::SwitchInline { query: "dom casmurro".to_string(), current_chat: false });
+    assert_eq!(keyboard.button("Search").unwrap().kind, ButtonKind::SwitchInline { query: String::new(), current_chat: true });
+
+    match &keyboard.button("Shelf").unwrap().kind {
+        ButtonKind::Callback(data) => assert_eq!(Shelf::decode(std::str::from_utf8(data).unwrap()), Some(Shelf { id: 7, page: 2 })),
+        kind => panic!("Unexpected kind {:?}", kind),
+    }
+    assert_eq!(Shelf::decode("about"), None);
+
+    // Telegram refuses the whole message otherwise
+    assert!(Keyboard::builder().button(Button::callback("Long", "a".repeat(65))).build().is_err());
+}
+
+#[test]
+fn paginates() {
+    let paginator = Paginator::for_items("search", 3, 170, 10);
+    assert_eq!(paginator.pages(), 17);
+    assert_eq!(paginator.range(170, 10), 20..30);
+
+    let keyboard = Keyboard::builder()
+        .button(Button::callback("Dom Casmurro", "book 1"))
+        .paginator(&paginator)
+        .button(Button::callback("Back", "start"))
+        .build()
+        .unwrap();
+    assert_eq!(texts(&keyboard), vec![vec!["Dom Casmurro"], vec!["«", "‹", "3/17", "›", "»"], vec!["Back"]]);
+    assert_eq!(keyboard.button("‹").unwrap().kind, ButtonKind::Callback(b"search 2".to_vec()));
+    assert_eq!(keyboard.button("»").unwrap().kind, ButtonKind::Callback(b"search 17".to_vec()));
+    assert_eq!(keyboard.button("3/17").unwrap().kind, ButtonKind::Callback(b"search noop".to_vec()));
+    assert_eq!(Paginator::parse("search", "search noop"), None);
+
+    // Only the buttons that lead somewhere
+    let labels = |paginator: Paginator| paginator.buttons().into_iter().map(|button| button.text).collect::<Vec<String>>();
+    assert_eq!(labels(Paginator::new("search", 1, 17)), vec!["1/17", "›", "»"]);
+    assert_eq!(labels(Paginator::new("search", 40, 17)), vec!["«", "‹", "17/17"]);
+    assert!(labels(Paginator::for_items("search", 1, 4, 10)).is_empty());
+
+    assert_eq!(Paginator::parse("search", "search 12"), Some(12));
+    assert_eq!(Paginator::parse("search", "searches 12"), None);
+    assert_eq!(Paginator::for_items("search", 17, 165, 10).range(165, 10), 160..165);
+}
@@ -0,0 +1,111 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use myne_books::language::{self, format, preference::Preference};
+
+
+fn portuguese() -> language::I18n<'static> {
+    let mut lang = language::new();
+    lang.set_language("pt-rBR");
+
+    lang
+}
+
+#[test]
+fn resolves_language_codes() {
+    assert_eq!(language::resolve("pt-rBR"), "pt-rBR");
+    assert_eq!(language::resolve("pt-br"), "pt-rBR");
+    assert_eq!(language::resolve("pt"), "pt-rBR");
+    assert_eq!(language::resolve("en"), "en-GB");
+    assert_eq!(language::resolve("de"), "en-GB");
+}
+
+#[test]
+fn falls_back_to_the_default_language() {
+    assert_eq!(portuguese().chain(), vec!["pt-rBR", "en-GB"]);
+    assert_eq!(language::new().chain(), vec!["en-GB"]);
+
+    // A language without a locale uses the default one
+    let lang = language::new().get_language("de");
+    assert!(lang.get_text("texts.start", vec![("bot_username", "MyneBot")]).starts_with("Welcome to the @MyneBot"));
+
+    // A key no locale has is given back as is
+    assert_eq!(portuguese().get_text("texts.nothing", vec![]), "texts.nothing");
+}
+
+#[test]
+fn set_language_changes_the_texts() {
+    let text = portuguese().get_text("texts.start", vec![("bot_username", "MyneBot")]);
+
+    assert!(text.starts_with("Bem-vindo ao @MyneBot"));
+}
+
+#[test]
+fn plurals() {
+    let english = language::new();
+    assert_eq!(english.get_plural("counts.users", 1, vec![]), "<b>1</b> user");
+    assert_eq!(english.get_plural("counts.users", 0, vec![]), "<b>0</b> users");
+    assert_eq!(english.get_plural("counts.users", 1500, vec![]), "<b>1,500</b> users");
+
+    let portuguese = portuguese();
+    assert_eq!(portuguese.get_plural("counts.groups", 0, vec![]), "<b>0</b> grupo");
+    assert_eq!(portuguese.get_plural("counts.groups", 2, vec![]), "<b>2</b> grupos");
+}
+
+#[test]
+fn numbers_and_dates() {
+    assert_eq!(language::new().format_number(1234567.5), "1,234,567.5");
+    assert_eq!(portuguese().format_number(1234567.5), "1.234.567,5");
+    assert_eq!(portuguese().format_number(-42.0), "-42");
+
+    assert_eq!(language::new().format_date(0), "1 January 1970");
+    assert_eq!(language::new().format_date(951782400), "29 February 2000");
+    assert_eq!(portuguese().format_date(1666137600), "19 de outubro de 2022");
+}
+
+#[test]
+fn named_arguments() {
+    let lang = language::new();
+    let items = [("name", "Myne"), ("count", "1000"), ("date", "0")];
+
+    assert_eq!(format::named(&lang, "{name} read {count:number} books", &items), "Myne read 1,000 books");
+    assert_eq!(format::named(&lang, "since {date:date}", &items), "since 1 January 1970");
+    assert_eq!(format::named(&lang, "{{name}} and {missing}", &items), "{name} and {missing}");
+}
+
+#[test]
+fn escapes_values_in_html_texts() {
+    let title = r#"<b>Myne & "the" Bookworm</b>"#;
+
+    let text = language::new().get_text("texts.welcome", vec![("title", title), ("prefixes", "<code>/</code>"), ("bot_username", "MyneBot")]);
+    assert!(text.contains("<b>&lt;b&gt;Myne &amp; &quot;the&quot; Bookworm&lt;/b&gt;</b>"));
+    // `{prefixes:raw}` is already HTML
+    assert!(text.contains("<code>/</code>"));
+
+    let items = [("title", "<i>Ehrenfest</i>")];
+    assert_eq!(format::named(&language::new(), "{title}", &items), "&lt;i&gt;Ehrenfest&lt;/i&gt;");
+    assert_eq!(format::named(&language::new(), "{title:raw}", &items), "<i>Ehrenfest</i>");
+    // Numbers and dates that do not parse are escaped too
+    assert_eq!(format::named(&language::new(), "{title:number} {title:date}", &items), "&lt;i&gt;Ehrenfest&lt;/i&gt; &lt;i&gt;Ehrenfest&lt;/i&gt;");
+
+    // Plain text is not escaped
+    let mut lang = language::new();
+    lang.set_escape_html(false);
+    assert_eq!(format::named(&lang, "{title}", &items), "<i>Ehrenfest</i>");
+}
+
+#[test]
+fn books_in_the_languages_of_the_user_come_first() {
+    let books = vec![("Dom Casmurro", "pt"), ("Emma", "en"), ("Don Quijote", "es"), ("Unknown", ""), ("O Alienista", "pt-BR")];
+
+    let preference = Preference::new("pt-rBR", &["en".to_string()], true);
+    assert_eq!(preference.languages(), ["pt", "en"]);
+    let sorted = preference.sort(books.clone(), |book| book.1);
+    assert_eq!(sorted.iter().map(|book| book.0).collect::<Vec<&str>>(), vec!["Dom Casmurro", "O Alienista", "Emma", "Don Quijote", "Unknown"]);
+
+    // Books without a language are kept when the other languages are hidden
+    let preference = Preference::new("pt-rBR", &[], false);
+    assert_eq!(preference.rank("es"), None);
+    let sorted = preference.sort(books, |book| book.1);
+    assert_eq!(sorted.iter().map(|book| book.0).collect::<Vec<&str>>(), vec!["Dom Casmurro", "O Alienista", "Unknown"]);
+}
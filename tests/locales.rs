@@ -0,0 +1,111 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+
+use myne_books::language::{self, check, format, loader};
+
+
+const DIRECTORY: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/locales");
+const SOURCES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src");
+
+fn sources(directory: &Path, files: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(directory).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            sources(&path, files);
+        } else if path.extension().map(|extension| extension == "rs").unwrap_or(false) {
+            files.push(path);
+        }
+    }
+}
+
+#[test]
+fn every_locale_is_complete() {
+    let reports = check::check(DIRECTORY, language::new().get_language_code()).unwrap();
+
+    for report in reports {
+        assert!(!report.has_errors(), "{:#?}", report);
+    }
+}
+
+#[test]
+fn every_locale_is_bundled() {
+    let locales = loader::load_dir(DIRECTORY).unwrap();
+
+    assert_eq!(locales.keys().map(String::as_str).collect::<Vec<&str>>(), language::LOCALES);
+}
+
+#[test]
+fn every_key_used_in_the_code_exists() {
+    let locales = loader::load_dir(DIRECTORY).unwrap();
+    let texts = &locales[language::new().get_language_code()];
+
+    // Keys are written whole, like `"texts.start"`, the sections come from the default locale
+    let mut sections = texts.keys()
+        .filter_map(|key| key.split('.').next())
+        .collect::<Vec<&str>>();
+    sections.dedup();
+    let key_regex = Regex::new(&format!(r#""((?:{})\.[a-z0-9_.]+)""#, sections.join("|"))).unwrap();
+
+    let mut files = Vec::new();
+    sources(Path::new(SOURCES), &mut files);
+
+    let mut unknown = Vec::new();
+    for file in files {
+        let content = fs::read_to_string(&file).unwrap();
+        for (number, line) in content.lines().enumerate() {
+            for captures in key_regex.captures_iter(line) {
+                let key = &captures[1];
+                // Plurals are used by their parent key
+                if !texts.contains_key(key) && !texts.contains_key(&format!("{}.other", key)) {
+                    unknown.push(format!("{}:{}: {}", file.display(), number + 1, key));
+                }
+            }
+        }
+    }
+
+    assert!(unknown.is_empty(), "Unknown keys:\n{}", unknown.join("\n"));
+}
+
+#[test]
+fn reports_placeholder_mismatches() {
+    let directory = std::env::temp_dir().join(format!("myne_books-locales-{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    fs::write(directory.join("en-GB.yml"), "en-GB:\n  texts:\n    about: \"<b>{bot_name}</b> reads {count:number} books\"\n    start: Hello\n").unwrap();
+    fs::write(directory.join("pt-rBR.yml"), "pt-rBR:\n  texts:\n    about: \"<b>{nome_do_bot}</b> lê {count} livros\"\n    help: Ajuda\n").unwrap();
+
+    let reports = check::check(&directory, "en-GB").unwrap();
+    fs::remove_dir_all(&directory).unwrap();
+
+    let report = &reports[0];
+    assert_eq!(report.locale, "pt-rBR");
+    assert_eq!(report.missing, vec!["texts.start"]);
+    assert_eq!(report.extra, vec!["texts.help"]);
+    assert_eq!(report.placeholders.len(), 1);
+    assert_eq!(report.placeholders[0].key, "texts.about");
+    assert_eq!(report.placeholders[0].missing, vec!["bot_name", "count:number"]);
+    assert_eq!(report.placeholders[0].extra, vec!["count", "nome_do_bot"]);
+    assert_eq!(report.errors(), 3);
+}
+
+#[test]
+fn finds_placeholders() {
+    let placeholders = format::placeholders("{{literal}} {name}, { count : number } and {title:raw}");
+
+    assert_eq!(placeholders.into_iter().collect::<Vec<String>>(), vec!["count:number", "name", "title:raw"]);
+}
+
+#[test]
+fn writes_texts_as_locale_files() {
+    let locales = loader::load_dir(DIRECTORY).unwrap();
+    let texts = &locales["pt-rBR"];
+
+    let yaml = loader::to_yaml("pt-rBR", texts).unwrap();
+    let parsed = loader::parse(&yaml).unwrap();
+
+    assert_eq!(&parsed["pt-rBR"], texts);
+}
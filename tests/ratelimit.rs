@@ -0,0 +1,45 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use myne_books::config::{self, Config};
+use myne_books::utils::RateLimiter;
+
+
+#[test]
+fn peeking_does_not_take_tokens() {
+    let limiter = RateLimiter::new(2, 0.001);
+
+    assert!(limiter.peek(1));
+    assert!(limiter.peek(1));
+    assert!(limiter.check(1));
+    assert!(limiter.check(1));
+    assert!(!limiter.peek(1));
+    assert!(!limiter.check(1));
+
+    // Every key has its own bucket
+    assert!(limiter.check(2));
+}
+
+// Get the problems of the limits in a configuration
+fn problems(config: &Config) -> Vec<String> {
+    match config.validate() {
+        Err(config::Error::Invalid(problems)) => problems.into_iter().filter(|problem| problem.contains("limits.")).collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[test]
+fn bursts_need_a_rate() {
+    let mut config = Config::default();
+    assert!(problems(&config).is_empty());
+
+    config.limits.user_rate = 0.0;
+    assert_eq!(problems(&config).len(), 1);
+
+    // No limit at all is fine
+    config.limits.user_burst = 0;
+    assert!(problems(&config).is_empty());
+
+    config.limits.chat_rate = -1.0;
+    assert_eq!(problems(&config).len(), 1);
+}
@@ -0,0 +1,243 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use std::fs;
+use std::path::Path;
+
+use myne_books::api::mock::{self, MockApi, Request};
+use myne_books::api::{self, CallbackAnswer, Chat};
+use myne_books::config;
+use myne_books::database;
+use myne_books::handler;
+use myne_books::language;
+use myne_books::reader::{self, toc::{self, Chapter, Contents, Navigation, CHAPTERS_PER_PAGE}};
+use myne_books::utils::{ButtonKind, Keyboard, Payload};
+
+
+fn contents() -> Contents {
+    let mut chapters = vec![
+        Chapter::new("Prologue", 1),
+        Chapter::new("Part 1", 5)
+            .child(Chapter::new("Chapter 1", 5))
+            .child(Chapter::new("Chapter 2", 20)
+                .child(Chapter::new("Interlude", 31))),
+    ];
+    for chapter in 3..=12 {
+        chapters.push(Chapter::new(format!("Chapter {}", chapter), chapter * 10 + 20));
+    }
+    chapters.push(Chapter::new("A very long epilogue about the temple library and its books", 200));
+
+    Contents::new(chapters)
+}
+
+fn fixture(name: &str) -> Contents {
+    toc::from_file(&Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)).unwrap()
+}
+
+fn texts(keyboard: &Keyboard) -> Vec<Vec<&str>> {
+    keyboard.rows.iter()
+        .map(|row| row.iter().map(|button| button.text.as_str()).collect())
+        .collect()
+}
+
+#[test]
+fn flattens_the_chapters() {
+    let contents = contents();
+    let entries = contents.entries();
+
+    assert_eq!(entries.len(), 16);
+    assert_eq!(entries.iter().take(5).map(|entry| (entry.title, entry.depth)).collect::<Vec<_>>(), vec![
+        ("Prologue", 0),
+        ("Part 1", 0),
+        ("Chapter 1", 1),
+        ("Chapter 2", 1),
+        ("Interlude", 2),
+    ]);
+
+    // The deepest chapter starting on the page is the one being read
+    assert_eq!(contents.current(5), Some(2));
+    assert_eq!(contents.current(33), Some(4));
+    assert_eq!(contents.current(0), None);
+    assert_eq!(contents.page_of(200), 2);
+    assert_eq!(contents.last_page(), 200);
+    assert_eq!(Contents::default().last_page(), 1);
+    assert!(Contents::default().is_empty());
+}
+
+#[test]
+fn builds_the_contents_keyboard() {
+    let contents = contents();
+    let lang = language::new();
+
+    let keyboard = contents.keyboard(lang, 7, 22, 1).unwrap();
+    assert_eq!(keyboard.rows.len(), CHAPTERS_PER_PAGE + 2);
+    assert_eq!(texts(&keyboard)[..5], [
+        vec!["Prologue"],
+        vec!["Part 1"],
+        vec!["· Chapter 1"],
+        vec!["· ▶ Chapter 2"],
+        vec!["· · Interlude"],
+    ]);
+    assert_eq!(texts(&keyboard)[CHAPTERS_PER_PAGE], vec!["1/2", "›", "»"]);
+    // The last row goes back to the page being read
+    let back = keyboard.rows.last().unwrap();
+    assert_eq!(back[0].kind, ButtonKind::Callback(b"read 7 22".to_vec()));
+
+    // Pressing a chapter opens its first page
+    let button = keyboard.button("· ▶ Chapter 2").unwrap();
+    assert_eq!(button.kind, ButtonKind::Callback(b"read 7 20".to_vec()));
+    assert_eq!(Navigation::decode("read 7 20"), Some(Navigation::Read { book: 7, page: 20 }));
+
+    // The paginator keeps the page being read
+    let keyboard = contents.keyboard(lang, 7, 22, 2).unwrap();
+    let previous = keyboard.button("‹").unwrap();
+    assert_eq!(previous.kind, ButtonKind::Callback(b"contents 7 22 1".to_vec()));
+    assert_eq!(Navigation::decode("contents 7 22 1"), Some(Navigation::Contents { book: 7, reading: 22, page: 1 }));
+    assert_eq!(texts(&keyboard)[keyboard.rows.len() - 2], vec!["«", "‹", "2/2"]);
+    assert_eq!(keyboard.button("2/2").unwrap().kind, ButtonKind::Callback(b"contents 7 22 noop".to_vec()));
+    assert!(keyboard.button("A very long epilogue about the temple l…").is_some());
+
+    assert_eq!(Navigation::decode("read 7"), None);
+    assert_eq!(Navigation::decode("read 7 20 1"), None);
+
+    // The button of the reader opens the page with the chapter being read
+    let button = contents.button(lang, 7, 200);
+    assert_eq!(button.kind, ButtonKind::Callback(b"contents 7 200 2".to_vec()));
+}
+
+#[test]
+fn reads_the_epub_navigation() {
+    // The pages are the files in the reading order, a heading without a link starts at its first chapter
+    assert_eq!(fixture("book.epub"), Contents::new(vec![
+        Chapter::new("Prologue", 1),
+        Chapter::new("Part 1", 3)
+            .child(Chapter::new("Chapter 1", 3))
+            .child(Chapter::new("Chapter 2", 4)),
+        Chapter::new("Epilogue", 5),
+    ]));
+
+    // EPUB 2 books only have the NCX
+    assert_eq!(fixture("book-ncx.epub"), Contents::new(vec![
+        Chapter::new("Prologue", 1),
+        Chapter::new("Part 1", 2)
+            .child(Chapter::new("Chapter 1", 3))
+            .child(Chapter::new("Chapter 2", 4)),
+        Chapter::new("Epilogue", 5),
+    ]));
+}
+
+#[test]
+fn reads_the_fb2_sections() {
+    // The sections without a title are left out but still take a page, the notes are not chapters
+    assert_eq!(fixture("book.fb2"), Contents::new(vec![
+        Chapter::new("Prologue", 1),
+        Chapter::new("Part 1 The temple", 2)
+            .child(Chapter::new("Chapter 1", 3))
+            .child(Chapter::new("Chapter 2", 5)),
+        Chapter::new("Epilogue", 6),
+    ]));
+}
+
+#[test]
+fn reads_the_pdf_outline() {
+    // In the order of the outline, by destinations, names and actions; the link to a website is not a chapter
+    assert_eq!(fixture("book.pdf"), Contents::new(vec![
+        Chapter::new("Prologue", 1),
+        Chapter::new("Part 1", 2)
+            .child(Chapter::new("Chapter 1", 2))
+            .child(Chapter::new("Capítulo 2", 4)),
+        Chapter::new("Epilogue", 6),
+    ]));
+
+    assert!(toc::from_file(Path::new("book.txt")).is_err());
+}
+
+// Use a database and a books directory of their own, with the EPUB of the fixtures as the book 7
+fn setup() {
+    let path = std::env::temp_dir().join(format!("myne_books-reader-{}.db3", std::process::id()));
+    database::set_path(&path.to_string_lossy());
+    database::connect().unwrap().initialize().unwrap();
+
+    let books = std::env::temp_dir().join(format!("myne_books-books-{}", std::process::id()));
+    fs::create_dir_all(&books).unwrap();
+    fs::copy(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/book.epub"), books.join("7.epub")).unwrap();
+
+    let mut decoded = (*config::get()).clone();
+    decoded.storage.books = books.to_string_lossy().to_string();
+    config::set(decoded);
+}
+
+async fn handle(client: &MockApi, update: api::Update) {
+    let mut handler_list = Vec::new();
+    handler::initialize(&mut handler_list).unwrap();
+
+    handler::handle_update(client, update, &handler_list, &["/".to_string()], &api::User::new(1000, "Myne")).await.unwrap();
+}
+
+fn data(message: &api::OutgoingMessage, text: &str) -> Vec<u8> {
+    match &message.keyboard.as_ref().unwrap().button(text).unwrap().kind {
+        ButtonKind::Callback(data) => data.clone(),
+        kind => panic!("Unexpected button {:?}", kind),
+    }
+}
+
+#[tokio::test]
+async fn the_contents_open_from_the_reader() {
+    setup();
+    assert_eq!(reader::find(7).unwrap().extension().unwrap(), "epub");
+    assert_eq!(reader::find(8), None);
+
+    let client = MockApi::new();
+    let user = api::User::new(90, "Hannelore");
+    let chat = Chat::User(user.clone());
+
+    // The fourth file of the book is the second chapter of the first part
+    handle(&client, mock::private_message(900, user.clone(), "/read 7 4")).await;
+    let contents = match client.take_requests().as_slice() {
+        [Request::SendMessage { message, .. }] => {
+            assert!(message.text.contains("<b>Chapter 2</b>"));
+            assert!(message.text.contains("4 of 5"));
+            data(message, "Contents 📑")
+        }
+        requests => panic!("Unexpected requests {:?}", requests),
+    };
+    assert_eq!(contents, b"contents 7 4 1".to_vec());
+
+    let chapter = match handle_callback(&client, 901, &chat, &user, &contents).await.as_slice() {
+        [Request::EditMessage { message_id, message, .. }, Request::AnswerCallback { .. }] => {
+            assert_eq!(*message_id, Some(901));
+            assert!(message.keyboard.as_ref().unwrap().button("· ▶ Chapter 2").is_some());
+            data(message, "Epilogue")
+        }
+        requests => panic!("Unexpected requests {:?}", requests),
+    };
+
+    // Pressing a chapter jumps to its first page
+    match handle_callback(&client, 901, &chat, &user, &chapter).await.as_slice() {
+        [Request::EditMessage { message, .. }, Request::AnswerCallback { .. }] => {
+            assert!(message.text.contains("<b>Epilogue</b>"));
+            assert!(message.text.contains("5 of 5"));
+            assert_eq!(data(message, "Contents 📑"), b"contents 7 5 1".to_vec());
+            assert_eq!(data(message, "‹"), b"read 7 4".to_vec());
+        }
+        requests => panic!("Unexpected requests {:?}", requests),
+    }
+
+    // A book that was removed
+    assert_eq!(handle_callback(&client, 902, &chat, &user, b"read 8 1").await, vec![
+        Request::AnswerCallback {
+            chat_id: 90,
+            answer: CallbackAnswer {
+                text: Some(language::new().get_text("errors.book_not_found", vec![]).trim().to_string()),
+                alert: true,
+                ..Default::default()
+            },
+        },
+    ]);
+}
+
+async fn handle_callback(client: &MockApi, message_id: i32, chat: &Chat, user: &api::User, data: &[u8]) -> Vec<Request> {
+    handle(client, mock::callback(message_id, chat.clone(), user.clone(), std::str::from_utf8(data).unwrap())).await;
+
+    client.take_requests()
+}
@@ -0,0 +1,86 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+
+use myne_books::language::{self, runtime};
+
+
+lazy_static! {
+    // The loaded locales are global, so the tests take turns
+    static ref LOCK: Mutex<()> = Mutex::new(());
+}
+
+fn directory(name: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("myne_books-{}-{}", name, std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+
+    directory
+}
+
+#[test]
+fn loaded_locales_come_before_the_bundled_ones() {
+    let _lock = LOCK.lock().unwrap();
+    let directory = directory("override");
+    fs::write(directory.join("en-GB.yml"), "en-GB:\n  texts:\n    start: \"Hello from @{bot_username}\"\n").unwrap();
+    fs::write(directory.join("es-ES.yml"), "es-ES:\n  texts:\n    start: \"Bienvenido a @{bot_username}\"\n").unwrap();
+
+    let mut codes = runtime::load(&directory).unwrap();
+    codes.sort_unstable();
+    assert_eq!(codes, vec!["en-GB", "es-ES"]);
+
+    let english = language::new();
+    assert_eq!(english.get_text("texts.start", vec![("bot_username", "MyneBot")]), "Hello from @MyneBot");
+    // Texts the directory does not have are still bundled
+    assert!(english.get_text("texts.about", vec![("bot_name", "Myne")]).contains("<b>Myne</b>"));
+
+    // New languages can be used right away
+    assert_eq!(language::resolve("es"), "es-ES");
+    let spanish = language::new().get_language(language::resolve("es-es"));
+    assert_eq!(spanish.get_text("texts.start", vec![("bot_username", "MyneBot")]), "Bienvenido a @MyneBot");
+    assert_eq!(spanish.chain(), vec!["es-ES", "en-GB"]);
+
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn changed_files_are_reloaded() {
+    let _lock = LOCK.lock().unwrap();
+    let directory = directory("reload");
+    let file = directory.join("pt-rBR.yml");
+    fs::write(&file, "pt-rBR:\n  texts:\n    start: Oi\n").unwrap();
+
+    runtime::load(&directory).unwrap();
+    assert!(!runtime::reload_if_changed().unwrap());
+
+    let portuguese = language::new().get_language("pt-rBR");
+    assert_eq!(portuguese.get_text("texts.start", vec![]), "Oi");
+
+    fs::write(&file, "pt-rBR:\n  texts:\n    start: Olá, leitor\n").unwrap();
+    assert!(runtime::reload_if_changed().unwrap());
+    assert_eq!(portuguese.get_text("texts.start", vec![]), "Olá, leitor");
+
+    // A broken file keeps the texts loaded before, and is reported once
+    fs::write(&file, "pt-rBR:\n  texts: [broken\n").unwrap();
+    assert!(runtime::reload_if_changed().is_err());
+    assert!(!runtime::reload_if_changed().unwrap());
+    assert_eq!(portuguese.get_text("texts.start", vec![]), "Olá, leitor");
+
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn locales_that_are_not_language_codes_are_skipped() {
+    let _lock = LOCK.lock().unwrap();
+    let directory = directory("codes");
+    fs::write(directory.join("de-DE.yml"), "de-DE:\n  texts:\n    start: Hallo\n\"<b>de</b>\":\n  texts:\n    start: Hallo\n").unwrap();
+
+    assert_eq!(runtime::load(&directory).unwrap(), vec!["de-DE"]);
+    assert_eq!(runtime::locales(), vec!["de-DE"]);
+
+    fs::remove_dir_all(&directory).unwrap();
+}
@@ -0,0 +1,58 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2022 Andriel Ferreira <https://github.com/AndrielFR>
+
+use myne_books::utils::text;
+
+
+#[test]
+fn counts_like_telegram() {
+    assert_eq!(text::length("<b>Ascendance</b> of a Bookworm"), 24);
+    assert_eq!(text::length("Myne &amp; Lutz &#128214;"), 14);
+    // Emojis are two UTF-16 units
+    assert_eq!(text::length("📖"), 2);
+}
+
+#[test]
+fn truncates_without_breaking_html() {
+    let html = "Myne <b>loves &amp; reads</b> books";
+
+    assert_eq!(text::truncate(html, 100), html);
+    // At the end of a word, closing the tags left open
+    assert_eq!(text::truncate(html, 16), "Myne <b>loves &amp;…</b>");
+    // Entities are never cut
+    assert_eq!(text::truncate("&amp;&amp;&amp;&amp;", 3), "&amp;&amp;…");
+    // Without a space in the second half the word is cut
+    assert_eq!(text::truncate("<i>Bookworm</i>", 5), "<i>Book…</i>");
+}
+
+#[test]
+fn splits_without_breaking_html() {
+    let html = "<b>First paragraph</b>\n\nSecond <i>one, a bit longer</i>";
+
+    assert_eq!(text::split(html, 100), vec![html]);
+    // At the end of the paragraph
+    assert_eq!(text::split(html, 30), vec!["<b>First paragraph</b>", "Second <i>one, a bit longer</i>"]);
+    // At the end of words, opening the tags again
+    assert_eq!(text::split(html, 20), vec!["<b>First paragraph</b>", "Second <i>one, a bit</i>", "<i>longer</i>"]);
+
+    let parts = text::split(&"Myne &amp; Lutz ".repeat(400), text::MESSAGE_LIMIT);
+    assert_eq!(parts.len(), 2);
+    assert!(parts.iter().all(|part| text::length(part) <= text::MESSAGE_LIMIT));
+    assert!(parts.iter().all(|part| part.starts_with("Myne") && part.ends_with("Lutz")));
+}
+
+#[test]
+fn validates_html_for_telegram() {
+    assert_eq!(text::validate("<b>Myne</b> &amp; <a href=\"https://t.me\">Lutz</a> &#128214;"), Ok(()));
+
+    assert_eq!(text::validate("Myne & Lutz"), Err("&".to_string()));
+    assert_eq!(text::validate("1 < 2"), Err("<".to_string()));
+    assert_eq!(text::validate("&nbsp;"), Err("&nbsp;".to_string()));
+    assert_eq!(text::validate("<b>Myne"), Err("<b>".to_string()));
+    assert_eq!(text::validate("<b><i>Myne</b></i>"), Err("</b>".to_string()));
+    assert_eq!(text::validate("<div>Myne</div>"), Err("<div>".to_string()));
+
+    assert!(!text::has_markup("Myne & Lutz"));
+    assert!(text::has_markup("Myne &amp; Lutz"));
+    assert!(text::has_markup("<b>Myne</b>"));
+}